@@ -1,28 +1,62 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use libr2fa::Key;
+use libr2fa::{HMACType, Key};
 
-pub fn otp_get_code_bench(c: &mut Criterion) {
-    let mut hotp_key = libr2fa::HOTPKey {
-        key: "MZZHI6LHOVUGU===".to_string(),
-        counter: black_box(0),
-        hmac_type: libr2fa::HMACType::SHA512,
-        ..Default::default()
-    };
-    c.bench_function("hotp sha512 get code", |b| {
-        b.iter(|| black_box(hotp_key.get_code().unwrap()))
-    });
-    drop(hotp_key);
+const ALGORITHMS: [(&str, HMACType); 3] = [
+    ("sha1", HMACType::SHA1),
+    ("sha256", HMACType::SHA256),
+    ("sha512", HMACType::SHA512),
+];
+
+pub fn hotp_get_code_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hotp get code");
+    for (name, hmac_type) in ALGORITHMS {
+        let mut hotp_key = libr2fa::HOTPKey {
+            key: "MZZHI6LHOVUGU===".to_string(),
+            hmac_type,
+            ..Default::default()
+        };
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                hotp_key.counter = black_box(0);
+                black_box(hotp_key.get_code().unwrap())
+            })
+        });
+    }
+    group.finish();
+}
 
-    let mut totp_key = libr2fa::TOTPKey {
-        key: "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=".to_string(),
-        hmac_type: libr2fa::HMACType::SHA512,
-        ..Default::default()
-    };
-    c.bench_function("totp sha512 get code", |b| {
-        b.iter(|| black_box(totp_key.get_code().unwrap()))
+pub fn totp_get_code_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("totp get code");
+    for (name, hmac_type) in ALGORITHMS {
+        let totp_key = libr2fa::TOTPKey {
+            key: "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A="
+                .to_string(),
+            hmac_type,
+            ..Default::default()
+        };
+        group.bench_function(name, |b| {
+            b.iter(|| black_box(totp_key.get_code_at(black_box(1_700_000_000)).unwrap()))
+        });
+    }
+    group.finish();
+}
+
+#[cfg(feature = "steam")]
+pub fn steam_get_code_bench(c: &mut Criterion) {
+    let steam_key =
+        libr2fa::SteamKey::from_shared_secret("test", "1Yl+tt/6w2dZEG51M8P6oc2x/cY=").unwrap();
+    c.bench_function("steam get code", |b| {
+        b.iter(|| black_box(steam_key.token.generate_code(black_box(1_700_000_000))))
     });
-    drop(totp_key);
 }
 
-criterion_group!(benches, otp_get_code_bench);
+#[cfg(not(feature = "steam"))]
+pub fn steam_get_code_bench(_: &mut Criterion) {}
+
+criterion_group!(
+    benches,
+    hotp_get_code_bench,
+    totp_get_code_bench,
+    steam_get_code_bench
+);
 criterion_main!(benches);
@@ -7,6 +7,11 @@ pub enum Error {
     ///
     /// if the key type is totp or hotp the the key should be base32 encoded
     InvalidKey,
+    /// invalid base32 secret
+    ///
+    /// with a description of the error,
+    /// e.g. the decoded length if it is not the expected length
+    InvalidBase32(String),
     /// Invalid digits
     ///
     /// if the digits is not 6, 7 or 8 for hotp or totp
@@ -28,6 +33,16 @@ pub enum Error {
     /// the third string is the serde error
     #[cfg(feature = "steam")]
     SteamSerdeError(String, String, String),
+    /// error in decrypting an encrypted steam mafile
+    ///
+    /// with a description of the error
+    #[cfg(feature = "steam")]
+    SteamDecryptError(String),
+    /// error in importing keys from another authenticator
+    ///
+    /// with a description of the error
+    #[cfg(feature = "interop")]
+    ImportError(String),
     /// io error
     ///
     /// the first string is the error message
@@ -42,6 +57,7 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::InvalidKey => write!(f, "Invalid key"),
+            Error::InvalidBase32(s) => write!(f, "Invalid base32: {}", s),
             Error::InvalidDigits => write!(f, "Invalid digits"),
             Error::InvalidURI(s) => write!(f, "Invalid URI: {}", s),
             Error::InvalidPath(s) => write!(f, "Invalid path: {}", s),
@@ -49,6 +65,10 @@ impl std::fmt::Display for Error {
             Error::SteamSerdeError(s1, s2, s3) => {
                 write!(f, "Steam serde error: {}, {}, {}", s1, s2, s3)
             }
+            #[cfg(feature = "steam")]
+            Error::SteamDecryptError(s) => write!(f, "Steam decrypt error: {}", s),
+            #[cfg(feature = "interop")]
+            Error::ImportError(s) => write!(f, "Import error: {}", s),
             Error::IOError(s1, s2, s3) => write!(f, "IO error: {}, {}, {}", s1, s2, s3),
         }
     }
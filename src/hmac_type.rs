@@ -48,7 +48,7 @@ impl HMACType {
         let result = match self {
             HMACType::SHA1 => {
                 let mac = Hmac::<sha1::Sha1>::new_from_slice(key);
-                if let Err(_) = mac {
+                if mac.is_err() {
                     return Err(error::Error::InvalidKey);
                 }
                 let mut mac = mac.unwrap();
@@ -57,10 +57,10 @@ impl HMACType {
                 let result = mac.finalize();
                 let result: &[u8] = &result.into_bytes();
                 Rc::from(result)
-            },
+            }
             HMACType::SHA256 => {
                 let mac = Hmac::<sha2::Sha256>::new_from_slice(key);
-                if let Err(_) = mac {
+                if mac.is_err() {
                     return Err(error::Error::InvalidKey);
                 }
                 let mut mac = mac.unwrap();
@@ -69,10 +69,10 @@ impl HMACType {
                 let result = mac.finalize();
                 let result: &[u8] = &result.into_bytes();
                 Rc::from(result)
-            },
+            }
             HMACType::SHA512 => {
                 let mac = Hmac::<sha2::Sha512>::new_from_slice(key);
-                if let Err(_) = mac {
+                if mac.is_err() {
                     return Err(error::Error::InvalidKey);
                 }
                 let mut mac = mac.unwrap();
@@ -81,7 +81,7 @@ impl HMACType {
                 let result = mac.finalize();
                 let result: &[u8] = &result.into_bytes();
                 Rc::from(result)
-            },
+            }
         };
 
         Ok(result)
@@ -1,8 +1,8 @@
-use std::rc::Rc;
+use std::{rc::Rc, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{error, HMACType, Key, OtpAuthKey};
+use crate::{error, HMACType, Key, OtpAuthKey, Secret};
 
 /// HOTPKey is the key for the HOTP,
 /// HOTP is the counter based key,
@@ -31,6 +31,8 @@ pub struct HOTPKey {
     pub key: String,
     /// digits
     /// 6, 7, 8
+    ///
+    /// `get_code` returns `Error::InvalidDigits` for any other value
     pub digits: u8,
     /// counter
     pub counter: u64,
@@ -57,18 +59,411 @@ impl Default for HOTPKey {
 }
 
 impl HOTPKey {
-    fn decode_key(&self) -> Result<Rc<[u8]>, error::Error> {
-        let key = data_encoding::BASE32.decode(self.get_key().as_bytes());
-        if key.is_err() {
+    /// create a key with the given secret and the default settings
+    ///
+    /// returns `Error::InvalidKey` if the secret is empty or not valid in its encoding
+    ///
+    /// ```rust
+    /// use libr2fa::HOTPKey;
+    /// use libr2fa::Secret;
+    ///
+    /// let key = HOTPKey::from_secret(Secret::Raw(b"12345678901234567890".to_vec())).unwrap();
+    ///
+    /// assert_eq!(key.key, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+    /// ```
+    pub fn from_secret(secret: Secret) -> Result<Self, error::Error> {
+        let key = secret.to_encoded()?;
+        if key.is_empty() {
             return Err(error::Error::InvalidKey);
         }
 
-        Ok(Rc::from(key.unwrap().as_slice()))
+        Ok(Self {
+            key,
+            ..Default::default()
+        })
+    }
+
+    /// whether the keys are for the same account,
+    /// comparing the secret, name, issuer and algorithm
+    ///
+    /// unlike `==`, this ignores the other settings and state,
+    /// such as the counter and the recovery codes,
+    /// e.g. to find duplicates when merging imported keys
+    ///
+    /// the secrets are compared decoded, so the same secret encoded differently still matches
+    ///
+    /// ```rust
+    /// use libr2fa::HOTPKey;
+    ///
+    /// let a = HOTPKey {
+    ///     name: "john.doe@email.com".to_string(),
+    ///     key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let b = HOTPKey {
+    ///     counter: 4,
+    ///     recovery_codes: vec!["a2b3-c4d5-e6f7".to_string()],
+    ///     ..a.clone()
+    /// };
+    ///
+    /// assert_ne!(a, b);
+    /// assert!(a.same_account(&b));
+    /// ```
+    pub fn same_account(&self, other: &Self) -> bool {
+        let same_secret = match (self.decode_key(), other.decode_key()) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => self.key == other.key,
+        };
+
+        same_secret
+            && self.name == other.name
+            && self.issuer == other.issuer
+            && self.hmac_type == other.hmac_type
+    }
+
+    fn decode_key(&self) -> Result<Rc<[u8]>, error::Error> {
+        let key = Secret::Encoded(self.get_key().to_string()).to_bytes()?;
+
+        Ok(Rc::from(key.as_slice()))
     }
 
     fn get_key(&self) -> &str {
         &self.key
     }
+
+    /// skip `by` counters without generating codes,
+    /// e.g. to resynchronize with a server whose counter has moved ahead
+    ///
+    /// the counter saturates at `u64::MAX`
+    ///
+    /// ```rust
+    /// use libr2fa::HOTPKey;
+    /// use libr2fa::Key;
+    ///
+    /// let mut hotp_key = HOTPKey {
+    ///     key: "MZZHI6LHOVUGU===".to_string(),
+    ///     counter: 4,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// hotp_key.advance(10);
+    ///
+    /// assert_eq!(hotp_key.get_counter(), Some(14));
+    /// ```
+    pub fn advance(&mut self, by: u64) {
+        self.counter = self.counter.saturating_add(by);
+    }
+
+    /// set the counter back to 0
+    ///
+    /// ```rust
+    /// use libr2fa::HOTPKey;
+    /// use libr2fa::Key;
+    ///
+    /// let mut hotp_key = HOTPKey {
+    ///     key: "MZZHI6LHOVUGU===".to_string(),
+    ///     counter: 4,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// hotp_key.reset();
+    ///
+    /// assert_eq!(hotp_key.get_counter(), Some(0));
+    /// ```
+    pub fn reset(&mut self) {
+        self.counter = 0;
+    }
+
+    /// get the next code as an integer,
+    /// the value before it is zero padded to `digits` length by `get_code`
+    ///
+    /// like `get_code`, this increments the counter,
+    /// returns `Error::InvalidKey` if the counter is already `u64::MAX`
+    ///
+    /// ```rust
+    /// use libr2fa::HOTPKey;
+    ///
+    /// let mut hotp_key = HOTPKey {
+    ///     key: "MZZHI6LHOVUGU===".to_string(),
+    ///     counter: 4,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let value = hotp_key.get_code_value().unwrap();
+    ///
+    /// assert!(value < 1_000_000);
+    /// assert_eq!(hotp_key.counter, 5);
+    /// ```
+    pub fn get_code_value(&mut self) -> Result<u32, error::Error> {
+        // validate before advancing, so an invalid key does not use up a counter
+        if !(6..=8).contains(&self.digits) {
+            return Err(error::Error::InvalidDigits);
+        }
+        self.decode_key()?;
+        let counter = self.next_counter()?;
+        self.counter = counter;
+
+        self.get_code_value_for_counter(counter)
+    }
+
+    /// get the next code, persisting the new counter before it is returned
+    ///
+    /// the counter is incremented and `persist` is called with the new counter,
+    /// the code is only returned if `persist` succeeds,
+    /// otherwise the counter is left unchanged and the error of `persist` is returned,
+    /// so a code is never handed out for a counter which was not stored
+    ///
+    /// `persist` is not called if the code can not be generated
+    ///
+    /// ```rust
+    /// use libr2fa::HOTPKey;
+    /// use libr2fa::Error;
+    ///
+    /// let mut hotp_key = HOTPKey {
+    ///     key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let mut stored = 0;
+    /// let code = hotp_key.get_code_and_persist(|counter| {
+    ///     stored = counter;
+    ///     Ok(())
+    /// });
+    ///
+    /// assert_eq!(code, Ok("287082".to_string()));
+    /// assert_eq!(stored, 1);
+    ///
+    /// let code = hotp_key.get_code_and_persist(|_| {
+    ///     Err(Error::IOError("could not store".to_string(), "".to_string(), "".to_string()))
+    /// });
+    ///
+    /// assert!(code.is_err());
+    /// assert_eq!(hotp_key.counter, 1);
+    /// ```
+    pub fn get_code_and_persist<F>(&mut self, mut persist: F) -> Result<String, error::Error>
+    where
+        F: FnMut(u64) -> Result<(), error::Error>,
+    {
+        let counter = self.next_counter()?;
+        let code = self.get_code_value_for_counter(counter)?;
+
+        persist(counter)?;
+        self.counter = counter;
+
+        Ok(self.format_code(code))
+    }
+
+    /// list the next `count` codes with their counters,
+    /// without advancing the counter
+    ///
+    /// the first pair is the code the next `get_code` returns,
+    /// e.g. for printing a list of backup codes,
+    /// the list stops early at the counter `u64::MAX`, the last one with a code
+    ///
+    /// ```rust
+    /// use libr2fa::HOTPKey;
+    /// use libr2fa::Key;
+    ///
+    /// let mut hotp_key = HOTPKey {
+    ///     key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let codes = hotp_key.preview_codes(3).unwrap();
+    ///
+    /// // rfc 4226 test vectors
+    /// assert_eq!(codes, vec![
+    ///     (1, "287082".to_string()),
+    ///     (2, "359152".to_string()),
+    ///     (3, "969429".to_string()),
+    /// ]);
+    /// assert_eq!(hotp_key.counter, 0);
+    /// assert_eq!(hotp_key.get_code().unwrap(), "287082");
+    /// ```
+    pub fn preview_codes(&self, count: u64) -> Result<Vec<(u64, String)>, error::Error> {
+        (1..=count)
+            .map_while(|i| self.counter.checked_add(i))
+            .map(|counter| {
+                let code = self.get_code_value_for_counter(counter)?;
+
+                Ok((counter, self.format_code(code)))
+            })
+            .collect()
+    }
+
+    /// verify a code against the next counter, the one the next `get_code` uses
+    ///
+    /// the code checked is the one for `counter + 1`, not for the current `counter`,
+    /// which is the counter of the last code already generated or verified,
+    /// on a match the counter is consumed, set to `counter + 1`, so the code can not be used again,
+    /// on a mismatch the counter is left unchanged,
+    /// the codes are compared in constant time
+    ///
+    /// this is the usual single use HOTP login check,
+    /// it has no look ahead window and never resynchronizes the counter itself,
+    /// so a client which generated codes without using them fails every check
+    /// until it is resynchronized: find the counter of its code with `preview_codes`,
+    /// then move to the counter before it with `advance` or `set_counter`,
+    /// after which `verify` accepts the code and the counters agree again
+    ///
+    /// ```rust
+    /// use libr2fa::HOTPKey;
+    ///
+    /// let mut hotp_key = HOTPKey {
+    ///     key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// // rfc 4226 test vectors for the counters 1 and 2
+    /// assert_eq!(hotp_key.verify("359152"), Ok(false));
+    /// assert_eq!(hotp_key.counter, 0);
+    ///
+    /// assert_eq!(hotp_key.verify("287082"), Ok(true));
+    /// assert_eq!(hotp_key.counter, 1);
+    ///
+    /// // the code is single use
+    /// assert_eq!(hotp_key.verify("287082"), Ok(false));
+    /// assert_eq!(hotp_key.verify("359152"), Ok(true));
+    ///
+    /// // the client skipped the code for the counter 3,
+    /// // resynchronize to the counter of the code for 4
+    /// assert_eq!(hotp_key.verify("338314"), Ok(false));
+    /// let (counter, _) = hotp_key
+    ///     .preview_codes(10)
+    ///     .unwrap()
+    ///     .into_iter()
+    ///     .find(|(_, code)| code == "338314")
+    ///     .unwrap();
+    /// assert_eq!(counter, 4);
+    /// hotp_key.advance(counter - 1 - hotp_key.counter);
+    /// assert_eq!(hotp_key.verify("338314"), Ok(true));
+    /// assert_eq!(hotp_key.counter, 4);
+    /// ```
+    pub fn verify(&mut self, code: &str) -> Result<bool, error::Error> {
+        let counter = self.next_counter()?;
+        let expected = self.get_code_value_for_counter(counter)?;
+
+        if !crate::util::constant_time_eq(&self.format_code(expected), code) {
+            return Ok(false);
+        }
+        self.counter = counter;
+
+        Ok(true)
+    }
+
+    /// the counter after the current one,
+    /// `Error::InvalidKey` if the counter is exhausted at `u64::MAX`
+    fn next_counter(&self) -> Result<u64, error::Error> {
+        match self.counter.checked_add(1) {
+            Some(counter) => Ok(counter),
+            None => Err(error::Error::InvalidKey),
+        }
+    }
+
+    /// generate the code value for the given counter
+    fn get_code_value_for_counter(&self, counter: u64) -> Result<u32, error::Error> {
+        if !(6..=8).contains(&self.digits) {
+            return Err(error::Error::InvalidDigits);
+        }
+        let raw = self.decode_key()?;
+
+        let res = self
+            .hmac_type
+            .get_hash(raw.as_ref(), &counter.to_be_bytes())?;
+        let code = crate::util::dynamic_truncate(&res)?;
+
+        // trim to the number of digits
+        Ok(code % 10u32.pow(self.digits as u32))
+    }
+
+    /// format the code value as a zero padded string of `digits` length
+    fn format_code(&self, code: u32) -> String {
+        let mut code = code.to_string();
+        // padding 0
+        while code.len() < self.digits as usize {
+            code.insert(0, '0');
+        }
+
+        code
+    }
+
+    /// build the key from the uri struct
+    fn from_uri(uri: &crate::URI) -> Self {
+        let counter = uri.counter.unwrap_or(30);
+        let digits = uri.digits.unwrap_or(6);
+        let algorithm = uri.algorithm.unwrap_or(HMACType::SHA1);
+
+        HOTPKey {
+            name: uri.name.clone(),
+            key: uri.secret.clone(),
+            digits,
+            counter,
+            recovery_codes: Vec::default(),
+            hmac_type: algorithm,
+            issuer: uri.issuer.clone(),
+        }
+    }
+}
+
+/// parse the key from a `otpauth://hotp/` uri string
+///
+/// returns `Error::InvalidURI` if the uri is not a HOTP uri
+///
+/// ```rust
+/// use libr2fa::HOTPKey;
+/// use libr2fa::HMACType;
+///
+/// let key: HOTPKey = "otpauth://hotp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co&algorithm=SHA256&digits=7&counter=7".parse().unwrap();
+///
+/// assert_eq!(key.digits, 7);
+/// assert_eq!(key.hmac_type, HMACType::SHA256);
+/// assert_eq!(key.counter, 7);
+///
+/// let key = "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".parse::<HOTPKey>();
+///
+/// assert!(key.is_err());
+/// ```
+impl FromStr for HOTPKey {
+    type Err = error::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let uri = crate::URI::from_str(s)?;
+
+        Self::try_from(&uri)
+    }
+}
+
+/// build the key from a parsed uri struct, keeping the concrete type,
+/// unlike `OtpAuthKey::from_uri_struct`
+///
+/// returns `Error::InvalidURI` if the uri is not a HOTP uri
+///
+/// ```rust
+/// use libr2fa::HOTPKey;
+/// use libr2fa::URI;
+///
+/// let uri = URI::from("otpauth://hotp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&counter=7");
+///
+/// let key = HOTPKey::try_from(&uri).unwrap();
+/// assert_eq!(key.name, "john.doe@email.com");
+///
+/// let uri = URI::from("otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ");
+///
+/// assert!(HOTPKey::try_from(&uri).is_err());
+/// ```
+impl TryFrom<&crate::URI> for HOTPKey {
+    type Error = error::Error;
+
+    fn try_from(uri: &crate::URI) -> Result<Self, Self::Error> {
+        if uri.key_type != crate::KeyType::HOTP {
+            return Err(error::Error::InvalidURI(format!(
+                "expected a hotp uri, got a {} uri",
+                uri.key_type
+            )));
+        }
+
+        Ok(Self::from_uri(uri))
+    }
 }
 
 impl OtpAuthKey for HOTPKey {
@@ -81,36 +476,15 @@ impl OtpAuthKey for HOTPKey {
             digits: Some(self.digits),
             period: None,
             counter: Some(self.counter),
-            key_type: crate::KeyType::TOTP,
+            key_type: crate::KeyType::HOTP,
+            epoch: None,
+            encoder: None,
+            image: None,
         }
     }
 
     fn from_uri_struct(uri: &crate::URI) -> Result<Box<dyn Key>, crate::Error> {
-        let counter = if let Some(counter) = uri.counter {
-            counter
-        } else {
-            30
-        };
-        let digits = if let Some(digits) = uri.digits {
-            digits
-        } else {
-            6
-        };
-        let algorithm = if let Some(algorithm) = uri.algorithm {
-            algorithm
-        } else {
-            HMACType::SHA1
-        };
-
-        Ok(Box::from(HOTPKey {
-            name: uri.name.clone(),
-            key: uri.secret.clone(),
-            digits,
-            counter,
-            recovery_codes: Vec::default(),
-            hmac_type: algorithm,
-            issuer: uri.issuer.clone(),
-        }))
+        Ok(Box::from(Self::from_uri(uri)))
     }
 
     fn get_issuer(&self) -> Option<&str> {
@@ -123,6 +497,43 @@ impl Key for HOTPKey {
         crate::KeyType::HOTP
     }
 
+    fn get_digits(&self) -> u8 {
+        self.digits
+    }
+
+    fn get_algorithm(&self) -> Option<HMACType> {
+        Some(self.hmac_type)
+    }
+
+    fn get_interval(&self) -> Option<u64> {
+        None
+    }
+
+    fn get_counter(&self) -> Option<u64> {
+        Some(self.counter)
+    }
+
+    fn get_code_at_time(&mut self, _: i64) -> Result<String, error::Error> {
+        self.get_code()
+    }
+
+    fn step_progress_from(&self, _: &dyn crate::TimeSource) -> Option<f64> {
+        None
+    }
+
+    fn security_advice(&self) -> Vec<String> {
+        let secret_len = self.decode_key().ok().map(|key| key.len());
+        crate::util::security_advice(self, secret_len)
+    }
+
+    fn provisioning_uri(&self) -> Result<String, error::Error> {
+        Ok(self.to_uri_struct().to_string())
+    }
+
+    fn sort_key(&self) -> (String, String) {
+        (self.issuer.clone().unwrap_or_default(), self.name.clone())
+    }
+
     fn get_name(&self) -> &str {
         &self.name
     }
@@ -132,35 +543,19 @@ impl Key for HOTPKey {
     }
 
     fn get_code(&mut self) -> Result<String, error::Error> {
-        let raw = self.decode_key()?;
-        self.counter += 1;
-
-        let res = self
-            .hmac_type
-            .get_hash(raw.as_ref(), &self.counter.to_be_bytes())?;
-        let offset: usize = (res[res.len() - 1] & 0x0f) as usize;
-
-        let code: u32 = (((res[offset] & 0x7f) as u32) << 24)
-            | ((res[offset + 1] as u32) << 16)
-            | ((res[offset + 2] as u32) << 8)
-            | (res[offset + 3] as u32);
+        let code = self.get_code_value()?;
 
-        // trim to the number of digits
-        let code = code % 10u32.pow(self.digits as u32);
-
-        let mut code = code.to_string();
-        // padding 0
-        while code.len() < self.digits as usize {
-            code.insert(0, '0');
-        }
-
-        Ok(code)
+        Ok(self.format_code(code))
     }
 
     fn set_name(&mut self, name: &str) {
         self.name = name.to_string();
     }
 
+    fn set_counter(&mut self, counter: u64) {
+        self.counter = counter;
+    }
+
     fn set_recovery_codes(&mut self, recovery_codes: Vec<String>) {
         self.recovery_codes = recovery_codes;
     }
@@ -168,4 +563,12 @@ impl Key for HOTPKey {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Key> {
+        Box::new(self.clone())
+    }
 }
@@ -0,0 +1,138 @@
+//! import keys from a [FreeOTP](https://freeotp.github.io) backup
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{Error, HMACType, Key, KeyType, Secret, URI};
+
+/// a token of the backup
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Token {
+    algo: Option<String>,
+    counter: Option<u64>,
+    digits: Option<u8>,
+    issuer_ext: Option<String>,
+    label: Option<String>,
+    period: Option<u64>,
+    /// the raw secret as java signed bytes
+    secret: Vec<i8>,
+    #[serde(rename = "type")]
+    token_type: Option<String>,
+}
+
+/// import the keys from a FreeOTP backup, in the order of its `tokenOrder`
+///
+/// the backup is the json form of the FreeOTP shared preferences,
+/// an object with a `tokenOrder` array of token ids,
+/// and each token stored under its id as an object or a json string,
+/// the `tokenOrder` array may be stored as a json string as well
+///
+/// FreeOTP stores the counter of a HOTP token as the next counter to use,
+/// while a `HOTPKey` stores the last used counter and generates the code for the one after it,
+/// so the imported key generates the same next code,
+/// except for a token which has not generated a code yet, with the next counter 0,
+/// whose imported key starts at counter 1, skipping the code for counter 0,
+/// which the look ahead window of HOTP servers accepts
+///
+/// ```rust
+/// use libr2fa::interop::freeotp;
+/// use libr2fa::KeyType;
+///
+/// let json = std::fs::read_to_string("./public/freeotp_backup_test.json").unwrap();
+///
+/// let keys = freeotp::import(&json).unwrap();
+///
+/// assert_eq!(keys.len(), 2);
+/// assert_eq!(keys[0].get_type(), KeyType::HOTP);
+/// assert_eq!(keys[1].get_type(), KeyType::TOTP);
+/// ```
+pub fn import(json: &str) -> Result<Vec<Box<dyn Key>>, Error> {
+    let backup: Result<Value, _> = serde_json::from_str(json);
+    if let Err(e) = backup {
+        return Err(Error::ImportError(format!(
+            "could not parse FreeOTP backup: {}",
+            e
+        )));
+    }
+    let backup = backup.unwrap();
+
+    let order: Vec<String> = match backup.get("tokenOrder") {
+        Some(order) => parse_embedded(order)?,
+        None => {
+            return Err(Error::ImportError(
+                "FreeOTP backup has no tokenOrder".to_string(),
+            ))
+        }
+    };
+
+    order
+        .iter()
+        .map(|id| match backup.get(id) {
+            Some(token) => token_to_key(parse_embedded(token)?),
+            None => Err(Error::ImportError(format!(
+                "FreeOTP backup has no token {}",
+                id
+            ))),
+        })
+        .collect()
+}
+
+/// parse a value which is stored either directly or as a json string
+fn parse_embedded<T: serde::de::DeserializeOwned>(value: &Value) -> Result<T, Error> {
+    let res = match value {
+        Value::String(s) => serde_json::from_str(s),
+        value => serde_json::from_value(value.clone()),
+    };
+    if let Err(e) = res {
+        return Err(Error::ImportError(format!(
+            "could not parse FreeOTP token: {}",
+            e
+        )));
+    }
+
+    Ok(res.unwrap())
+}
+
+fn token_to_key(token: Token) -> Result<Box<dyn Key>, Error> {
+    let token_type = token.token_type.unwrap_or_default().to_ascii_lowercase();
+    let key_type = match token_type.as_str() {
+        "" | "totp" => KeyType::TOTP,
+        "hotp" => KeyType::HOTP,
+        _ => {
+            return Err(Error::ImportError(format!(
+                "unsupported FreeOTP token type {}",
+                token_type
+            )))
+        }
+    };
+
+    let secret = token.secret.iter().map(|b| *b as u8).collect::<Vec<u8>>();
+    let secret = Secret::Raw(secret).to_encoded()?;
+
+    // FreeOTP stores the next counter, the key stores the last used one,
+    // the key can not start before counter 1, so a fresh token skips counter 0
+    let counter = match (key_type, token.counter.unwrap_or_default()) {
+        (KeyType::HOTP, 0) => Some(0),
+        (KeyType::HOTP, counter) => Some(counter - 1),
+        _ => None,
+    };
+    let period = match key_type {
+        KeyType::HOTP => None,
+        _ => token.period,
+    };
+
+    let uri = URI {
+        name: token.label.unwrap_or_default(),
+        key_type,
+        secret,
+        algorithm: token.algo.map(HMACType::from),
+        digits: token.digits,
+        counter,
+        period,
+        issuer: token.issuer_ext.filter(|issuer| !issuer.is_empty()),
+        ..Default::default()
+    };
+
+    crate::key_from_uri_struct(&uri)
+}
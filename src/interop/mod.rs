@@ -0,0 +1,7 @@
+//! import keys from the backups of other authenticators
+
+pub mod freeotp;
+pub mod twofas;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,104 @@
+use crate::{error::Error, HMACType, TOTPKey};
+
+#[test]
+fn test_twofas_import() -> Result<(), Error> {
+    let json = std::fs::read_to_string("./public/twofas_backup_test.2fas").unwrap();
+
+    let keys = super::twofas::import(&json);
+
+    // the second service is a steam token, which needs the steam feature
+    #[cfg(not(feature = "steam"))]
+    assert!(matches!(keys, Err(Error::ImportError(_))));
+
+    #[cfg(feature = "steam")]
+    {
+        let mut keys = keys?;
+        assert_eq!(keys.len(), 2);
+
+        let totp_key = keys[0].as_any().downcast_ref::<TOTPKey>().unwrap();
+        assert_eq!(totp_key.name, "john.doe@email.com");
+        assert_eq!(totp_key.issuer, Some("ACME Co".to_string()));
+        assert_eq!(totp_key.key, "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ");
+        assert_eq!(totp_key.digits, 7);
+        assert_eq!(totp_key.time_step, 60);
+        assert_eq!(totp_key.hmac_type, HMACType::SHA256);
+
+        assert_eq!(keys[1].get_type(), crate::KeyType::Steam);
+        assert_eq!(keys[1].get_name(), "test");
+
+        let mafile = crate::steam::MaFile::from_file("./public/mafile_test.mafile")?;
+        let mut steam_key = crate::SteamKey::from_mafile(mafile)?;
+        assert_eq!(keys[1].get_code()?, crate::Key::get_code(&mut steam_key)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_twofas_import_invalid() {
+    assert!(super::twofas::import("not json").is_err());
+    // encrypted backup
+    assert!(super::twofas::import(r#"{"servicesEncrypted": "abc", "schemaVersion": 4}"#).is_err());
+    assert!(super::twofas::import(
+        r#"{"services": [{"name": "a", "secret": "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ", "otp": {"tokenType": "YAOTP"}}]}"#
+    )
+    .is_err());
+
+    let keys = super::twofas::import(r#"{"services": []}"#).unwrap();
+    assert!(keys.is_empty());
+}
+
+#[test]
+fn test_freeotp_import() -> Result<(), Error> {
+    let json = std::fs::read_to_string("./public/freeotp_backup_test.json").unwrap();
+
+    let mut keys = super::freeotp::import(&json)?;
+    assert_eq!(keys.len(), 2);
+
+    // rfc 4226 test vector for the next counter, 1
+    assert_eq!(keys[0].get_name(), "jane.doe@email.com");
+    assert_eq!(keys[0].get_code()?, "287082");
+
+    let totp_key = keys[1].as_any().downcast_ref::<TOTPKey>().unwrap();
+    assert_eq!(totp_key.name, "john.doe@email.com");
+    assert_eq!(totp_key.issuer, Some("ACME Co".to_string()));
+    assert_eq!(totp_key.key, "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ");
+    assert_eq!(totp_key.digits, 7);
+    assert_eq!(totp_key.time_step, 60);
+    assert_eq!(totp_key.hmac_type, HMACType::SHA256);
+
+    // tokens and order stored as objects rather than json strings
+    let keys = super::freeotp::import(
+        r#"{"tokenOrder": ["a"], "a": {"label": "a", "secret": [49, 50, 51, 52, 53], "type": "TOTP"}}"#,
+    )?;
+    assert_eq!(keys.len(), 1);
+    assert_eq!(keys[0].get_name(), "a");
+
+    Ok(())
+}
+
+#[test]
+fn test_freeotp_import_new_hotp() -> Result<(), Error> {
+    let json = std::fs::read_to_string("./public/freeotp_backup_new_hotp_test.json").unwrap();
+
+    let mut keys = super::freeotp::import(&json)?;
+    assert_eq!(keys.len(), 1);
+
+    // a fresh token with the next counter 0 skips to counter 1,
+    // the rfc 4226 test vector for counter 0 is 755224
+    assert_eq!(keys[0].get_counter(), Some(0));
+    assert_eq!(keys[0].get_code()?, "287082");
+
+    Ok(())
+}
+
+#[test]
+fn test_freeotp_import_invalid() {
+    assert!(super::freeotp::import("not json").is_err());
+    assert!(super::freeotp::import(r#"{"a": {"secret": []}}"#).is_err());
+    assert!(super::freeotp::import(r#"{"tokenOrder": ["a"]}"#).is_err());
+    assert!(super::freeotp::import(
+        r#"{"tokenOrder": ["a"], "a": {"secret": [49], "type": "YAOTP"}}"#
+    )
+    .is_err());
+}
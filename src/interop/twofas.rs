@@ -0,0 +1,114 @@
+//! import keys from a plaintext [2FAS](https://2fas.com) backup
+
+use serde::Deserialize;
+
+use crate::{Error, HMACType, Key, KeyType, URI};
+
+/// the plaintext 2FAS backup file
+#[derive(Debug, Deserialize)]
+struct Backup {
+    /// missing for encrypted backups
+    services: Option<Vec<Service>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Service {
+    name: String,
+    secret: String,
+    #[serde(default)]
+    otp: Otp,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Otp {
+    account: Option<String>,
+    issuer: Option<String>,
+    digits: Option<u8>,
+    period: Option<u64>,
+    algorithm: Option<String>,
+    counter: Option<u64>,
+    token_type: Option<String>,
+}
+
+/// import the keys from a plaintext 2FAS backup
+///
+/// the `tokenType` of each service selects the key type,
+/// `TOTP` (the default), `HOTP`, or `STEAM` which builds a `SteamKey`
+///
+/// encrypted backups are not supported
+///
+/// ```rust
+/// use libr2fa::interop::twofas;
+/// use libr2fa::KeyType;
+///
+/// let json = std::fs::read_to_string("./public/twofas_backup_test.2fas").unwrap();
+///
+/// let keys = twofas::import(&json).unwrap();
+///
+/// assert_eq!(keys.len(), 2);
+/// assert_eq!(keys[0].get_type(), KeyType::TOTP);
+/// assert_eq!(keys[1].get_type(), KeyType::Steam);
+/// ```
+pub fn import(json: &str) -> Result<Vec<Box<dyn Key>>, Error> {
+    let backup: Result<Backup, _> = serde_json::from_str(json);
+    if let Err(e) = backup {
+        return Err(Error::ImportError(format!(
+            "could not parse 2FAS backup: {}",
+            e
+        )));
+    }
+    let backup = backup.unwrap();
+
+    let services = match backup.services {
+        Some(services) => services,
+        None => {
+            return Err(Error::ImportError(
+                "encrypted 2FAS backups are not supported".to_string(),
+            ))
+        }
+    };
+
+    services.into_iter().map(service_to_key).collect()
+}
+
+fn service_to_key(service: Service) -> Result<Box<dyn Key>, Error> {
+    let otp = service.otp;
+
+    let token_type = otp.token_type.unwrap_or_default().to_ascii_lowercase();
+    let key_type = match token_type.as_str() {
+        "" | "totp" => KeyType::TOTP,
+        "hotp" => KeyType::HOTP,
+        #[cfg(feature = "steam")]
+        "steam" => KeyType::Steam,
+        _ => {
+            return Err(Error::ImportError(format!(
+                "unsupported 2FAS token type {} for {}",
+                token_type, service.name
+            )))
+        }
+    };
+
+    let name = match otp.account {
+        Some(account) if !account.is_empty() => account,
+        _ => service.name.clone(),
+    };
+    let issuer = match otp.issuer {
+        Some(issuer) if !issuer.is_empty() => issuer,
+        _ => service.name,
+    };
+
+    let uri = URI {
+        name,
+        key_type,
+        secret: crate::normalize_base32(&service.secret)?,
+        algorithm: otp.algorithm.map(HMACType::from),
+        digits: otp.digits,
+        counter: otp.counter,
+        period: otp.period,
+        issuer: Some(issuer),
+        ..Default::default()
+    };
+
+    crate::key_from_uri_struct(&uri)
+}
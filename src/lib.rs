@@ -21,15 +21,39 @@ use serde::{Deserialize, Serialize};
 mod error;
 mod hmac_type;
 mod hotp;
+mod recovery_code;
+mod secret;
+mod stored_key;
+mod time_source;
 mod totp;
 mod uri;
+mod util;
 
 pub use error::Error;
 pub use hmac_type::HMACType;
 pub use hotp::HOTPKey;
+pub use recovery_code::generate_recovery_codes;
+pub use secret::generate_secret;
+pub use secret::is_valid_base32;
+pub use secret::normalize_base32;
+pub use secret::Secret;
+pub use secret::SecretEncoding;
+pub use secret::DEFAULT_SECRET_LENGTH;
+pub use secret::MIN_SECRET_LENGTH;
+pub use stored_key::StoredKey;
+pub use time_source::FixedTimeSource;
+pub use time_source::SystemTimeSource;
+pub use time_source::TimeSource;
+pub use totp::CodeDisplay;
 pub use totp::TOTPKey;
+pub use totp::TOTPKeyBuilder;
+pub use totp::VerifyResult;
+#[cfg(feature = "qrcodegen")]
+pub use uri::QrCodeOptions;
 pub use uri::URI;
 
+#[cfg(feature = "interop")]
+pub mod interop;
 #[cfg(feature = "steam")]
 pub mod steam;
 #[cfg(feature = "steam")]
@@ -41,13 +65,20 @@ mod test;
 /// KeyType is the type of the key
 /// HOTP is the counter based key
 /// TOTP is the time based key
-/// STEAM is the steam guard key (TODO not implemented yet)
+/// STEAM is the steam guard key
+///
+/// it is displayed and serialized in lowercase, as in otpauth uris,
+/// the capitalized variant names are still accepted when deserializing
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
 pub enum KeyType {
+    #[serde(alias = "HOTP")]
     HOTP,
     #[default]
+    #[serde(alias = "TOTP")]
     TOTP,
     #[cfg(feature = "steam")]
+    #[serde(alias = "Steam")]
     Steam,
 }
 
@@ -67,6 +98,8 @@ impl From<&str> for KeyType {
         match s.to_ascii_lowercase().as_str() {
             "hotp" => KeyType::HOTP,
             "totp" => KeyType::TOTP,
+            #[cfg(feature = "steam")]
+            "steam" => KeyType::Steam,
             _ => KeyType::default(),
         }
     }
@@ -116,11 +149,82 @@ pub trait Key {
     /// ```
     fn as_any(&self) -> &dyn Any;
 
+    /// use to downcast to original type mutably
+    ///
+    /// ```rust
+    /// use libr2fa::HOTPKey;
+    /// use libr2fa::TOTPKey;
+    /// use libr2fa::HMACType;
+    /// use libr2fa::Key;
+    ///
+    /// let hotp_key = HOTPKey {
+    ///     key: "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=".to_string(),
+    ///     hmac_type: HMACType::SHA1,
+    ///     counter: 10,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let mut key: Box<dyn Key> = Box::new(hotp_key);
+    ///
+    /// assert!(key.as_any_mut().downcast_mut::<TOTPKey>().is_none());
+    ///
+    /// let hotp_key = key.as_any_mut().downcast_mut::<HOTPKey>().unwrap();
+    /// hotp_key.counter = 0;
+    ///
+    /// assert_eq!(key.as_any().downcast_ref::<HOTPKey>().unwrap().counter, 0);
+    /// ```
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// clone the key into a new box
+    ///
+    /// used by the `Clone` implementation of `Box<dyn Key>`
+    ///
+    /// ```rust
+    /// use libr2fa::HOTPKey;
+    /// use libr2fa::HMACType;
+    /// use libr2fa::Key;
+    ///
+    /// let hotp_key = HOTPKey {
+    ///     key: "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=".to_string(),
+    ///     hmac_type: HMACType::SHA1,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let mut key1: Box<dyn Key> = Box::new(hotp_key);
+    /// let mut key2 = key1.clone();
+    ///
+    /// assert_eq!(key1.get_code(), key2.get_code());
+    /// ```
+    fn clone_box(&self) -> Box<dyn Key>;
+
     /// get_code returns the code for the key
     ///
     /// if it is HTOP key, it will increment the counter
     fn get_code(&mut self) -> Result<String, error::Error>;
 
+    /// get the code at the given unix time in seconds,
+    /// rather than the time of the local clock, e.g. to validate codes on a server
+    ///
+    /// HOTP keys ignore the time and behave like `get_code`, incrementing the counter,
+    /// the other keys return `Error::InvalidKey` for a time before their first step,
+    /// the `t0` of a TOTP key or the unix epoch for a steam key
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    /// use libr2fa::Key;
+    ///
+    /// let mut key: Box<dyn Key> = Box::new(TOTPKey {
+    ///     key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+    ///     digits: 8,
+    ///     ..Default::default()
+    /// });
+    ///
+    /// // rfc 6238 test vector
+    /// assert_eq!(key.get_code_at_time(59).unwrap(), "94287082");
+    /// assert!(key.get_code_at_time(-1).is_err());
+    /// ```
+    fn get_code_at_time(&mut self, unix_seconds: i64) -> Result<String, error::Error>;
+
     /// get the name of the key
     ///
     /// ```rust
@@ -163,6 +267,237 @@ pub trait Key {
     /// get the type of the key
     fn get_type(&self) -> KeyType;
 
+    /// get the key to sort the keys by, the issuer then the name,
+    /// the issuer is empty if there is none, and `Steam` for steam
+    ///
+    /// ```rust
+    /// use libr2fa::HOTPKey;
+    /// use libr2fa::TOTPKey;
+    /// use libr2fa::Key;
+    ///
+    /// let mut keys: Vec<Box<dyn Key>> = vec![
+    ///     Box::new(TOTPKey {
+    ///         name: "b".to_string(),
+    ///         issuer: Some("ACME Co".to_string()),
+    ///         ..Default::default()
+    ///     }),
+    ///     Box::new(HOTPKey {
+    ///         name: "a".to_string(),
+    ///         issuer: Some("ACME Co".to_string()),
+    ///         ..Default::default()
+    ///     }),
+    ///     Box::new(TOTPKey {
+    ///         name: "c".to_string(),
+    ///         ..Default::default()
+    ///     }),
+    /// ];
+    ///
+    /// keys.sort_by_key(|key| key.sort_key());
+    ///
+    /// let names: Vec<&str> = keys.iter().map(|key| key.get_name()).collect();
+    /// assert_eq!(names, vec!["c", "a", "b"]);
+    /// ```
+    fn sort_key(&self) -> (String, String);
+
+    /// whether the issuer of the key is `issuer`, ignoring case,
+    /// e.g. to pick the accounts of the service asking for a code
+    ///
+    /// steam keys always report the issuer `Steam`,
+    /// keys without an issuer match nothing
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    /// use libr2fa::Key;
+    ///
+    /// let keys: Vec<Box<dyn Key>> = vec![
+    ///     Box::new(TOTPKey {
+    ///         name: "a".to_string(),
+    ///         issuer: Some("ACME Co".to_string()),
+    ///         ..Default::default()
+    ///     }),
+    ///     Box::new(TOTPKey {
+    ///         name: "b".to_string(),
+    ///         ..Default::default()
+    ///     }),
+    /// ];
+    ///
+    /// let found: Vec<&str> = keys
+    ///     .iter()
+    ///     .filter(|key| key.matches_issuer("acme co"))
+    ///     .map(|key| key.get_name())
+    ///     .collect();
+    /// assert_eq!(found, vec!["a"]);
+    /// ```
+    fn matches_issuer(&self, issuer: &str) -> bool {
+        let (own, _) = self.sort_key();
+        !own.is_empty() && own.to_lowercase() == issuer.to_lowercase()
+    }
+
+    /// get the otpauth uri of the key, e.g. for a qrcode or an export
+    ///
+    /// the same as `OtpAuthKey::get_uri`, which is not available on a `Box<dyn Key>`
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    /// use libr2fa::Key;
+    ///
+    /// let key: Box<dyn Key> = Box::new(TOTPKey {
+    ///     name: "john.doe@email.com".to_string(),
+    ///     key: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
+    ///     ..Default::default()
+    /// });
+    ///
+    /// assert_eq!(
+    ///     key.provisioning_uri().unwrap(),
+    ///     "otpauth://totp/john.doe%40email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&algorithm=SHA1&digits=6&period=30"
+    /// );
+    /// ```
+    fn provisioning_uri(&self) -> Result<String, error::Error>;
+
+    /// whether the code changes with time,
+    /// true for TOTP and steam, false for HOTP, whose code changes with the counter
+    ///
+    /// a ui can show a countdown for time based keys,
+    /// and a button for the next code otherwise
+    ///
+    /// ```rust
+    /// use libr2fa::HOTPKey;
+    /// use libr2fa::TOTPKey;
+    /// use libr2fa::Key;
+    ///
+    /// let keys: Vec<Box<dyn Key>> = vec![
+    ///     Box::new(HOTPKey::default()),
+    ///     Box::new(TOTPKey::default()),
+    /// ];
+    ///
+    /// assert!(!keys[0].is_time_based());
+    /// assert!(keys[1].is_time_based());
+    /// ```
+    fn is_time_based(&self) -> bool {
+        !matches!(self.get_type(), KeyType::HOTP)
+    }
+
+    /// get the number of digits of the code
+    ///
+    /// always 5 for steam
+    fn get_digits(&self) -> u8;
+
+    /// get the hmac algorithm of the key
+    ///
+    /// `None` for steam, which always uses its own SHA1 based scheme
+    fn get_algorithm(&self) -> Option<HMACType>;
+
+    /// get the time step in seconds of the key
+    ///
+    /// `None` for HOTP, which is counter based,
+    /// always 30 for steam
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    /// use libr2fa::HMACType;
+    /// use libr2fa::Key;
+    ///
+    /// let totp_key = TOTPKey {
+    ///     key: "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=".to_string(),
+    ///     hmac_type: HMACType::SHA256,
+    ///     digits: 8,
+    ///     time_step: 60,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let key: Box<dyn Key> = Box::new(totp_key);
+    ///
+    /// assert_eq!(key.get_digits(), 8);
+    /// assert_eq!(key.get_algorithm(), Some(HMACType::SHA256));
+    /// assert_eq!(key.get_interval(), Some(60));
+    /// ```
+    fn get_interval(&self) -> Option<u64>;
+
+    /// get the counter of the key
+    ///
+    /// `None` for TOTP and steam, which are time based
+    ///
+    /// ```rust
+    /// use libr2fa::HOTPKey;
+    /// use libr2fa::Key;
+    ///
+    /// let hotp_key = HOTPKey {
+    ///     key: "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=".to_string(),
+    ///     counter: 4,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let mut key: Box<dyn Key> = Box::new(hotp_key);
+    ///
+    /// key.get_code().unwrap();
+    /// assert_eq!(key.get_counter(), Some(5));
+    /// ```
+    fn get_counter(&self) -> Option<u64>;
+
+    /// the fraction of the current time step which has elapsed, from 0.0 up to but not 1.0,
+    /// e.g. for a progress ring around the code
+    ///
+    /// `None` for HOTP, which is counter based,
+    /// steam keys use their fixed 30 second step
+    fn step_progress(&self) -> Option<f64> {
+        self.step_progress_from(&SystemTimeSource)
+    }
+
+    /// like `step_progress`, at the current time of the given time source
+    ///
+    /// ```rust
+    /// use libr2fa::FixedTimeSource;
+    /// use libr2fa::HOTPKey;
+    /// use libr2fa::TOTPKey;
+    /// use libr2fa::Key;
+    ///
+    /// let totp_key = TOTPKey::default();
+    ///
+    /// assert_eq!(totp_key.step_progress_from(&FixedTimeSource(60)), Some(0.0));
+    /// assert_eq!(totp_key.step_progress_from(&FixedTimeSource(75)), Some(0.5));
+    ///
+    /// assert_eq!(HOTPKey::default().step_progress_from(&FixedTimeSource(75)), None);
+    /// ```
+    fn step_progress_from(&self, time: &dyn TimeSource) -> Option<f64>;
+
+    /// human readable advice on the weaknesses of the key's settings,
+    /// e.g. to warn the user when adding it
+    ///
+    /// purely informational, derived from the algorithm, digits, period and secret length,
+    /// steam keys get no advice, as their scheme is fixed
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    /// use libr2fa::HMACType;
+    /// use libr2fa::Key;
+    ///
+    /// let totp_key = TOTPKey {
+    ///     key: "MFRGG===".to_string(),
+    ///     time_step: 60,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(
+    ///     totp_key.security_advice(),
+    ///     vec![
+    ///         "uses SHA1; SHA256 or SHA512 are stronger",
+    ///         "only 6 digits, 8 are harder to guess",
+    ///         "a 60 second period keeps each code valid for longer than the usual 30",
+    ///         "the secret is only 3 bytes, at least 10 are recommended",
+    ///     ]
+    /// );
+    ///
+    /// let totp_key = TOTPKey {
+    ///     key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+    ///     hmac_type: HMACType::SHA256,
+    ///     digits: 8,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert!(totp_key.security_advice().is_empty());
+    /// ```
+    fn security_advice(&self) -> Vec<String>;
+
     /// set the name of the key
     ///
     /// ```rust
@@ -183,6 +518,25 @@ pub trait Key {
     /// ```
     fn set_name(&mut self, name: &str);
 
+    /// set the counter of the key
+    ///
+    /// does nothing for TOTP and steam, which are time based
+    ///
+    /// ```rust
+    /// use libr2fa::HOTPKey;
+    /// use libr2fa::Key;
+    ///
+    /// let mut key: Box<dyn Key> = Box::new(HOTPKey {
+    ///     key: "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=".to_string(),
+    ///     ..Default::default()
+    /// });
+    ///
+    /// key.set_counter(42);
+    ///
+    /// assert_eq!(key.get_counter(), Some(42));
+    /// ```
+    fn set_counter(&mut self, counter: u64);
+
     /// set the recovery codes
     ///
     /// ```rust
@@ -202,10 +556,69 @@ pub trait Key {
     ///
     /// ```
     fn set_recovery_codes(&mut self, recovery_codes: Vec<String>);
+
+    /// replace the recovery codes with `count` newly generated ones
+    ///
+    /// see `generate_recovery_codes` for the format of the codes,
+    /// does nothing for steam, whose revocation code is issued by steam
+    ///
+    /// ```rust
+    /// use libr2fa::HOTPKey;
+    /// use libr2fa::Key;
+    ///
+    /// let mut hotp_key = HOTPKey {
+    ///     key: "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// hotp_key.regenerate_recovery_codes(10);
+    ///
+    /// assert_eq!(hotp_key.get_recovery_codes().len(), 10);
+    /// ```
+    fn regenerate_recovery_codes(&mut self, count: usize) {
+        self.set_recovery_codes(generate_recovery_codes(count, 4));
+    }
+}
+
+impl Clone for Box<dyn Key> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// create a new key of the uri's key type from the uri struct
+///
+/// the single place dispatching on the key type,
+/// which every function creating keys from uris uses
+///
+/// ```rust
+/// use libr2fa::key_from_uri_struct;
+/// use libr2fa::KeyType;
+/// use libr2fa::URI;
+///
+/// let uri = URI::from("otpauth://hotp/john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&counter=7");
+///
+/// let key = key_from_uri_struct(&uri).unwrap();
+///
+/// assert_eq!(key.get_type(), KeyType::HOTP);
+/// assert_eq!(key.get_counter(), Some(7));
+/// ```
+pub fn key_from_uri_struct(uri: &URI) -> Result<Box<dyn Key>, Error> {
+    match uri.key_type {
+        KeyType::HOTP => HOTPKey::from_uri_struct(uri),
+        KeyType::TOTP => TOTPKey::from_uri_struct(uri),
+        #[cfg(feature = "steam")]
+        KeyType::Steam => steam::SteamKey::from_uri_struct(uri),
+    }
 }
 
 /// create a new key from the uri string
 ///
+/// the uri is parsed with `URI::from_str_lenient`,
+/// so a uri without a secret or with invalid digits is rejected,
+/// but contradictory parameters are accepted,
+/// like the uris exported by some apps
+///
 /// ```rust
 /// use libr2fa::otpauth_from_uri;
 /// use libr2fa::TOTPKey;
@@ -219,7 +632,7 @@ pub trait Key {
 /// let mut totp_key1 = totp_key1.unwrap();
 ///
 /// let mut totp_key2 = TOTPKey {
-///     name: "ACME Co:john.doe@email.com".to_string(),
+///     name: "john.doe@email.com".to_string(),
 ///     key: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
 ///     digits: 7,
 ///     time_step: 60,
@@ -233,18 +646,55 @@ pub trait Key {
 /// assert_eq!(totp_key1.get_code(), totp_key2.get_code());
 /// ```
 pub fn otpauth_from_uri(uri: &str) -> Result<Box<dyn Key>, Error> {
-    let uri_struct = URI::from(uri);
+    let uri_struct = URI::from_str_lenient(uri)?;
 
-    match uri_struct.key_type {
-        KeyType::HOTP => HOTPKey::from_uri_struct(&uri_struct),
-        KeyType::TOTP => TOTPKey::from_uri_struct(&uri_struct),
-        #[cfg(feature = "steam")]
-        KeyType::Steam => steam::SteamKey::from_uri_struct(&uri_struct),
-    }
+    key_from_uri_struct(&uri_struct)
+}
+
+/// create the keys from a text with one otpauth uri per line,
+/// a common plain text backup
+///
+/// blank lines and comment lines starting with `#` are skipped,
+/// returns `Error::InvalidURI` with the line number of the first uri which can not be parsed
+///
+/// ```rust
+/// use libr2fa::import_uri_list;
+/// use libr2fa::Error;
+///
+/// let keys = import_uri_list(
+///     "# my accounts
+/// otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co
+///
+/// otpauth://hotp/jane.doe@email.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=0",
+/// ).unwrap();
+///
+/// assert_eq!(keys.len(), 2);
+///
+/// let keys = import_uri_list("otpauth://totp/john.doe@email.com?issuer=ACME%20Co");
+///
+/// assert_eq!(keys.err(), Some(Error::InvalidURI("line 1: missing secret".to_string())));
+/// ```
+pub fn import_uri_list(text: &str) -> Result<Vec<Box<dyn Key>>, Error> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| {
+            otpauth_from_uri(line).map_err(|e| {
+                let e = match e {
+                    Error::InvalidURI(e) => e,
+                    e => e.to_string(),
+                };
+                Error::InvalidURI(format!("line {}: {}", number, e))
+            })
+        })
+        .collect()
 }
 
 /// create a new key from the uri qrcode
 ///
+/// the uri in the qrcode is parsed like `otpauth_from_uri`
+///
 /// ```rust
 /// use libr2fa::otpauth_from_uri_qrcode;
 /// use libr2fa::TOTPKey;
@@ -258,7 +708,7 @@ pub fn otpauth_from_uri(uri: &str) -> Result<Box<dyn Key>, Error> {
 /// let mut totp_key1 = totp_key1.unwrap();
 ///
 /// let mut totp_key2 = TOTPKey {
-///     name: "ACME Co:john.doe@email.com".to_string(),
+///     name: "john.doe@email.com".to_string(),
 ///     issuer: Some("ACME Co".to_string()),
 ///     key: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
 ///     digits: 7,
@@ -273,14 +723,10 @@ pub fn otpauth_from_uri(uri: &str) -> Result<Box<dyn Key>, Error> {
 /// ```
 #[cfg(feature = "qrcoderead")]
 pub fn otpauth_from_uri_qrcode(path: &str) -> Result<Box<dyn Key>, Error> {
-    let uri_struct = URI::from_qr_code(path)?;
+    let uri_struct = URI::decode_qr_payload(path)?;
+    let uri_struct = URI::from_str_lenient(&uri_struct)?;
 
-    match uri_struct.key_type {
-        KeyType::HOTP => HOTPKey::from_uri_struct(&uri_struct),
-        KeyType::TOTP => TOTPKey::from_uri_struct(&uri_struct),
-        #[cfg(feature = "steam")]
-        KeyType::Steam => steam::SteamKey::from_uri_struct(&uri_struct),
-    }
+    key_from_uri_struct(&uri_struct)
 }
 
 pub trait OtpAuthKey {
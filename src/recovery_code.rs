@@ -0,0 +1,48 @@
+use rand::Rng;
+
+/// the alphabet of the recovery codes,
+/// lowercase letters and digits without the easily confused `0`, `1`, `i`, `l`, `o`
+const RECOVERY_CODE_ALPHABET: &[u8] = b"23456789abcdefghjkmnpqrstuvwxyz";
+
+/// the number of groups in a recovery code
+const RECOVERY_CODE_GROUPS: usize = 3;
+
+/// generate `count` random recovery codes
+///
+/// each code is 3 groups of `group_len` characters joined by `-`, e.g. `a2b3-c4d5-e6f7`,
+/// the characters are drawn from a cryptographically secure random generator
+/// out of lowercase letters and digits, excluding the easily confused `0`, `1`, `i`, `l` and `o`
+///
+/// recovery codes are one-time codes,
+/// the application should remove a code once it has been used
+///
+/// ```rust
+/// use libr2fa::generate_recovery_codes;
+///
+/// let codes = generate_recovery_codes(10, 4);
+///
+/// assert_eq!(codes.len(), 10);
+/// for code in codes {
+///     assert_eq!(code.len(), 14);
+///     assert_eq!(code.split('-').count(), 3);
+/// }
+/// ```
+pub fn generate_recovery_codes(count: usize, group_len: usize) -> Vec<String> {
+    let mut rng = rand::thread_rng();
+
+    (0..count)
+        .map(|_| {
+            (0..RECOVERY_CODE_GROUPS)
+                .map(|_| {
+                    (0..group_len)
+                        .map(|_| {
+                            RECOVERY_CODE_ALPHABET[rng.gen_range(0..RECOVERY_CODE_ALPHABET.len())]
+                                as char
+                        })
+                        .collect::<String>()
+                })
+                .collect::<Vec<String>>()
+                .join("-")
+        })
+        .collect()
+}
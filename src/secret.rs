@@ -0,0 +1,200 @@
+use rand::RngCore;
+
+use crate::error;
+
+/// the length in bytes of a generated secret, 160 bits as recommended by rfc 4226
+pub const DEFAULT_SECRET_LENGTH: usize = 20;
+
+/// the shortest secret in bytes accepted by a strict builder, e.g. `TOTPKeyBuilder::strict`
+///
+/// hmac accepts any key length, but an empty or one byte secret is almost
+/// always a mistake, which silently generates weak codes,
+/// the rfc test vectors use 20 byte secrets, so 10 bytes is conservative
+pub const MIN_SECRET_LENGTH: usize = 10;
+
+/// generate a random base32 encoded secret of `byte_len` bytes
+///
+/// the bytes are drawn from a cryptographically secure random generator,
+/// `DEFAULT_SECRET_LENGTH` is the common length
+///
+/// ```rust
+/// use libr2fa::generate_secret;
+/// use libr2fa::Secret;
+/// use libr2fa::DEFAULT_SECRET_LENGTH;
+///
+/// let secret = generate_secret(DEFAULT_SECRET_LENGTH);
+///
+/// assert_eq!(secret.len(), 32);
+/// assert_eq!(Secret::Encoded(secret).to_bytes().unwrap().len(), 20);
+/// ```
+pub fn generate_secret(byte_len: usize) -> String {
+    let mut secret = vec![0u8; byte_len];
+    rand::thread_rng().fill_bytes(&mut secret);
+
+    data_encoding::BASE32.encode(&secret)
+}
+
+/// whether `s` is valid base32 as it is,
+/// uppercase and padded to a multiple of 8 characters
+///
+/// see `normalize_base32` for secrets which are written differently
+///
+/// ```rust
+/// use libr2fa::is_valid_base32;
+///
+/// assert!(is_valid_base32("MFRGG==="));
+/// assert!(!is_valid_base32("MFRGG"));
+/// assert!(!is_valid_base32("mfrgg==="));
+/// assert!(!is_valid_base32("not base32!"));
+/// ```
+pub fn is_valid_base32(s: &str) -> bool {
+    data_encoding::BASE32.decode(s.as_bytes()).is_ok()
+}
+
+/// normalize a base32 secret as it is often written or exported,
+/// in lowercase, grouped with spaces, or without padding,
+/// to uppercase base32 padded to a multiple of 8 characters
+///
+/// returns `Error::InvalidBase32` if it is not valid base32 after normalizing
+///
+/// ```rust
+/// use libr2fa::normalize_base32;
+///
+/// assert_eq!(normalize_base32("mfrg g").unwrap(), "MFRGG===");
+/// assert!(normalize_base32("not base32!").is_err());
+/// ```
+pub fn normalize_base32(s: &str) -> Result<String, error::Error> {
+    let mut normalized = s
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '=')
+        .collect::<String>()
+        .to_ascii_uppercase();
+    while normalized.len() % 8 != 0 {
+        normalized.push('=');
+    }
+
+    let res = data_encoding::BASE32.decode(normalized.as_bytes());
+    if let Err(e) = res {
+        return Err(error::Error::InvalidBase32(e.to_string()));
+    }
+
+    Ok(normalized)
+}
+
+/// the encoding a secret is distributed in,
+/// base32 in otpauth uris, but some providers use base64 or hex
+///
+/// ```rust
+/// use libr2fa::SecretEncoding;
+///
+/// assert_eq!(SecretEncoding::Base32.decode("MFRGG===").unwrap(), b"abc".to_vec());
+/// assert_eq!(SecretEncoding::Base64.decode("YWJj").unwrap(), b"abc".to_vec());
+/// assert_eq!(SecretEncoding::Hex.decode("616263").unwrap(), b"abc".to_vec());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SecretEncoding {
+    #[default]
+    Base32,
+    Base64,
+    /// upper or lower case
+    Hex,
+}
+
+impl SecretEncoding {
+    /// decode the secret to its raw bytes, ignoring whitespace
+    ///
+    /// returns `Error::InvalidKey` if the secret is not valid in the encoding
+    pub fn decode(&self, secret: &str) -> Result<Vec<u8>, error::Error> {
+        let secret = secret
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>();
+
+        let res = match self {
+            SecretEncoding::Base32 => data_encoding::BASE32.decode(secret.as_bytes()),
+            SecretEncoding::Base64 => data_encoding::BASE64.decode(secret.as_bytes()),
+            SecretEncoding::Hex => data_encoding::HEXLOWER_PERMISSIVE.decode(secret.as_bytes()),
+        };
+        if res.is_err() {
+            return Err(error::Error::InvalidKey);
+        }
+
+        Ok(res.unwrap())
+    }
+}
+
+/// the secret of a HOTP or TOTP key
+///
+/// the keys store their secret base32 encoded,
+/// `Raw` lets the secret be given as bytes without encoding it by hand,
+/// and `Hex` as the hex some hardware token vendors provide
+///
+/// ```rust
+/// use libr2fa::Secret;
+///
+/// let encoded = Secret::Encoded("MFRGG===".to_string());
+/// let raw = Secret::Raw(b"abc".to_vec());
+/// let hex = Secret::Hex("616263".to_string());
+///
+/// assert_eq!(encoded.to_bytes().unwrap(), b"abc".to_vec());
+/// assert_eq!(raw.to_encoded().unwrap(), "MFRGG===");
+/// assert_eq!(hex.to_encoded().unwrap(), "MFRGG===");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Secret {
+    /// base32 encoded secret, as used in otpauth uris
+    Encoded(String),
+    /// raw secret bytes
+    Raw(Vec<u8>),
+    /// hex encoded secret, upper or lower case
+    Hex(String),
+}
+
+impl Secret {
+    /// get the raw bytes of the secret
+    ///
+    /// returns `Error::InvalidKey` if the encoded secret is not valid base32,
+    /// or the hex secret is not valid hex, e.g. of odd length
+    pub fn to_bytes(&self) -> Result<Vec<u8>, error::Error> {
+        match self {
+            Secret::Encoded(secret) => {
+                let secret = data_encoding::BASE32.decode(secret.as_bytes());
+                if secret.is_err() {
+                    return Err(error::Error::InvalidKey);
+                }
+
+                Ok(secret.unwrap())
+            }
+            Secret::Raw(secret) => Ok(secret.clone()),
+            Secret::Hex(secret) => SecretEncoding::Hex.decode(secret),
+        }
+    }
+
+    /// get the base32 encoded secret
+    ///
+    /// returns `Error::InvalidKey` if the encoded secret is not valid base32,
+    /// or the hex secret is not valid hex
+    pub fn to_encoded(&self) -> Result<String, error::Error> {
+        match self {
+            Secret::Encoded(secret) => {
+                self.to_bytes()?;
+
+                Ok(secret.clone())
+            }
+            Secret::Raw(secret) => Ok(data_encoding::BASE32.encode(secret)),
+            Secret::Hex(_) => Ok(data_encoding::BASE32.encode(&self.to_bytes()?)),
+        }
+    }
+}
+
+impl From<Vec<u8>> for Secret {
+    fn from(value: Vec<u8>) -> Self {
+        Secret::Raw(value)
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Secret::Encoded(value)
+    }
+}
@@ -0,0 +1,39 @@
+use rand::Rng;
+
+/// generate a random device id for steam guard enrollment
+///
+/// the id is in the `android:xxxxxxxx-xxxx-4xxx-yxxx-xxxxxxxxxxxx` format,
+/// a uuid v4 prefixed with `android:`, matching what the steam mobile app sends
+///
+/// steam binds the authenticator to this id,
+/// so the same device id must be reused when finalizing or removing the authenticator,
+/// store it alongside the account, e.g. in `MaFile::device_id`
+///
+/// ```rust
+/// use libr2fa::steam::generate_device_id;
+///
+/// let device_id = generate_device_id();
+///
+/// assert!(device_id.starts_with("android:"));
+/// assert_eq!(device_id.len(), "android:".len() + 36);
+/// ```
+pub fn generate_device_id() -> String {
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill(&mut bytes);
+
+    // uuid version 4
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    // uuid variant 1
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = data_encoding::HEXLOWER.encode(&bytes);
+
+    format!(
+        "android:{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
@@ -1,20 +1,61 @@
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// pbkdf2 iterations used by SteamDesktopAuthenticator
+const SDA_PBKDF2_ITERATIONS: u32 = 50000;
+/// aes key size in bytes used by SteamDesktopAuthenticator
+const SDA_KEY_SIZE: usize = 32;
+
+/// the mafile of a steam guard account
+///
+/// only `account_name` and `shared_secret` are required,
+/// maFiles written by different SDA versions omit some of the other fields,
+/// which are then left empty
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct MaFile {
     pub account_name: String,
+    #[serde(default)]
     pub device_id: String,
+    #[serde(default)]
     pub identity_secret: String,
+    #[serde(default)]
     pub revocation_code: String,
+    #[serde(default)]
     pub secret_1: String,
+    /// stored as either a number or a string
+    #[serde(default, deserialize_with = "deserialize_u64_from_string")]
     pub serial_number: u64,
+    /// stored as either a number or a string
+    #[serde(default, deserialize_with = "deserialize_u64_from_string")]
     pub server_time: u64,
     pub shared_secret: String,
+    #[serde(default)]
     pub status: u64,
+    #[serde(default)]
     pub token_gid: String,
+    #[serde(default)]
     pub uri: String,
 }
 
+/// deserialize a u64 stored as either a json number or a string,
+/// steam sends large numbers as strings
+fn deserialize_u64_from_string<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrU64 {
+        String(String),
+        U64(u64),
+    }
+
+    match StringOrU64::deserialize(deserializer)? {
+        StringOrU64::String(s) => s.parse::<u64>().map_err(serde::de::Error::custom),
+        StringOrU64::U64(n) => Ok(n),
+    }
+}
+
 impl MaFile {
     /// load a mafile from a string
     ///
@@ -117,6 +158,58 @@ impl MaFile {
         Self::from_string(&s.unwrap())
     }
 
+    /// load a mafile encrypted by SteamDesktopAuthenticator
+    ///
+    /// the `salt` and `iv` are the base64 encoded `encryption_salt` and `encryption_iv`
+    /// stored for the account in the SDA `manifest.json`,
+    /// the `passphrase` is the SDA encryption passphrase
+    ///
+    /// ```rust
+    /// use libr2fa::steam::MaFile;
+    ///
+    /// let mafile = MaFile::from_encrypted_file(
+    ///     "./public/mafile_encrypted_test.mafile",
+    ///     "passphrase",
+    ///     "AAECAwQFBgc=",
+    ///     "EBESExQVFhcYGRobHB0eHw==",
+    /// );
+    ///
+    /// assert!(mafile.is_ok());
+    ///
+    /// let mafile = mafile.unwrap();
+    ///
+    /// assert_eq!(mafile.account_name, "test");
+    /// assert_eq!(mafile.shared_secret, "1Yl+tt/6w2dZEG51M8P6oc2x/cY=");
+    ///
+    /// let mafile = MaFile::from_encrypted_file(
+    ///     "./public/mafile_encrypted_test.mafile",
+    ///     "wrong passphrase",
+    ///     "AAECAwQFBgc=",
+    ///     "EBESExQVFhcYGRobHB0eHw==",
+    /// );
+    ///
+    /// assert!(mafile.is_err());
+    /// ```
+    pub fn from_encrypted_file(
+        path: &str,
+        passphrase: &str,
+        salt: &str,
+        iv: &str,
+    ) -> Result<Self, crate::Error> {
+        let s = std::fs::read_to_string(path);
+        if let Err(e) = s {
+            return Err(crate::Error::IOError(
+                "Error in read mafile".to_string(),
+                path.to_string(),
+                e.to_string(),
+            ));
+        }
+
+        let s = decrypt_sda(s.unwrap().trim(), passphrase, salt, iv)?;
+
+        Self::from_string(&s)
+    }
+
     /// save a mafile to a string
     ///
     /// ```rust
@@ -203,3 +296,63 @@ impl MaFile {
         Ok(())
     }
 }
+
+/// decrypt a base64 encoded SteamDesktopAuthenticator payload
+///
+/// the key is derived from the passphrase with PBKDF2-HMAC-SHA1,
+/// the payload is encrypted with AES-256-CBC and PKCS7 padding
+fn decrypt_sda(data: &str, passphrase: &str, salt: &str, iv: &str) -> Result<String, crate::Error> {
+    let salt = data_encoding::BASE64.decode(salt.as_bytes());
+    if salt.is_err() {
+        return Err(crate::Error::SteamDecryptError(
+            "salt is not valid base64".to_string(),
+        ));
+    }
+    let salt = salt.unwrap();
+
+    let iv = data_encoding::BASE64.decode(iv.as_bytes());
+    if iv.is_err() {
+        return Err(crate::Error::SteamDecryptError(
+            "iv is not valid base64".to_string(),
+        ));
+    }
+    let iv = iv.unwrap();
+
+    let data = data_encoding::BASE64.decode(data.as_bytes());
+    if data.is_err() {
+        return Err(crate::Error::SteamDecryptError(
+            "mafile is not valid base64".to_string(),
+        ));
+    }
+    let mut data = data.unwrap();
+
+    let mut key = [0u8; SDA_KEY_SIZE];
+    pbkdf2::pbkdf2_hmac::<sha1::Sha1>(
+        passphrase.as_bytes(),
+        &salt,
+        SDA_PBKDF2_ITERATIONS,
+        &mut key,
+    );
+
+    let decryptor = cbc::Decryptor::<aes::Aes256>::new_from_slices(&key, &iv);
+    if decryptor.is_err() {
+        return Err(crate::Error::SteamDecryptError(
+            "iv should be 16 bytes".to_string(),
+        ));
+    }
+    let decrypted = decryptor.unwrap().decrypt_padded_mut::<Pkcs7>(&mut data);
+    if decrypted.is_err() {
+        return Err(crate::Error::SteamDecryptError(
+            "could not decrypt mafile, wrong passphrase?".to_string(),
+        ));
+    }
+
+    let s = String::from_utf8(decrypted.unwrap().to_vec());
+    if s.is_err() {
+        return Err(crate::Error::SteamDecryptError(
+            "decrypted mafile is not valid utf8, wrong passphrase?".to_string(),
+        ));
+    }
+
+    Ok(s.unwrap())
+}
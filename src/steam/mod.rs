@@ -1,9 +1,12 @@
+mod device_id;
 mod mafile;
 mod steam_key;
 mod token;
 
+pub use device_id::generate_device_id;
 pub use mafile::MaFile;
 pub use steam_key::SteamKey;
+pub use token::{STEAM_CODE_ALPHABET, STEAM_CODE_LENGTH};
 
 #[cfg(test)]
 mod test;
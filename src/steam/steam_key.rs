@@ -1,4 +1,6 @@
-use crate::{Error, Key, OtpAuthKey};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Key, OtpAuthKey, SystemTimeSource, TimeSource};
 
 use super::{token::TwoFactorSecret, MaFile};
 
@@ -27,6 +29,10 @@ use super::{token::TwoFactorSecret, MaFile};
 ///
 /// println!("steam code: {}", code);
 /// ```
+///
+/// the default key has an all zero secret and an empty mafile,
+/// it generates meaningless but deterministic codes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SteamKey {
     pub token: TwoFactorSecret,
     pub mafile: MaFile,
@@ -38,19 +44,216 @@ impl SteamKey {
 
         Ok(SteamKey { token, mafile })
     }
+
+    /// like `from_mafile`, but borrows the mafile,
+    /// for when it is still needed afterward
+    ///
+    /// the mafile is cloned into the key only if the shared secret is valid
+    ///
+    /// ```rust
+    /// use libr2fa::SteamKey;
+    /// use libr2fa::Key;
+    /// use libr2fa::steam::MaFile;
+    ///
+    /// let mafile = MaFile::from_file("./public/mafile_test.mafile").unwrap();
+    ///
+    /// let steam_key = SteamKey::from_mafile_ref(&mafile);
+    /// assert!(steam_key.is_ok());
+    ///
+    /// let steam_key = SteamKey::try_from(&mafile);
+    /// assert!(steam_key.is_ok());
+    ///
+    /// assert_eq!(steam_key.unwrap().get_name(), mafile.account_name);
+    /// ```
+    pub fn from_mafile_ref(mafile: &MaFile) -> Result<Self, Error> {
+        let token = TwoFactorSecret::parse_shared_secret(mafile.shared_secret.clone())?;
+
+        Ok(SteamKey {
+            token,
+            mafile: mafile.clone(),
+        })
+    }
+
+    /// create a key from the base64 `shared_secret` alone,
+    /// for when only the codes need to be generated
+    ///
+    /// the other fields of the mafile are left empty
+    ///
+    /// ```rust
+    /// use libr2fa::SteamKey;
+    /// use libr2fa::Key;
+    ///
+    /// let steam_key = SteamKey::from_shared_secret("test", "1Yl+tt/6w2dZEG51M8P6oc2x/cY=");
+    ///
+    /// assert!(steam_key.is_ok());
+    ///
+    /// let mut steam_key = steam_key.unwrap();
+    ///
+    /// assert_eq!(steam_key.get_name(), "test");
+    /// assert_eq!(steam_key.get_code().unwrap().len(), 5);
+    /// ```
+    pub fn from_shared_secret(account_name: &str, shared_secret: &str) -> Result<Self, Error> {
+        let mafile = MaFile {
+            account_name: account_name.to_string(),
+            shared_secret: shared_secret.to_string(),
+            ..Default::default()
+        };
+
+        SteamKey::from_mafile(mafile)
+    }
+
+    /// check the key is usable, e.g. after importing it
+    ///
+    /// the `shared_secret` of the mafile has to decode to the 20 byte secret of the token,
+    /// from base64 or, as some sources store it, base32,
+    /// and the code generated for the time 0 has to be 5 characters of the steam alphabet,
+    /// returns the error of parsing the shared secret, or `Error::InvalidKey` otherwise
+    ///
+    /// ```rust
+    /// use libr2fa::SteamKey;
+    ///
+    /// let steam_key = SteamKey::from_shared_secret("test", "1Yl+tt/6w2dZEG51M8P6oc2x/cY=").unwrap();
+    /// assert!(steam_key.validate().is_ok());
+    ///
+    /// assert!(SteamKey::default().validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), Error> {
+        let token = TwoFactorSecret::parse_stored_secret(self.mafile.shared_secret.clone())?;
+        // the token is public, and may have been changed without the mafile
+        if token.to_shared_secret() != self.token.to_shared_secret() {
+            return Err(Error::InvalidKey);
+        }
+
+        let code = self.token.generate_code(0);
+        if code.len() != super::STEAM_CODE_LENGTH
+            || !code
+                .bytes()
+                .all(|c| super::STEAM_CODE_ALPHABET.contains(&c))
+        {
+            return Err(Error::InvalidKey);
+        }
+
+        Ok(())
+    }
+
+    /// generate the code for the current time of the given time source
+    ///
+    /// `Key::get_code` is this with the system clock,
+    /// see `TimeSource`
+    ///
+    /// returns `Error::InvalidKey` if the time is before the unix epoch
+    ///
+    /// ```rust
+    /// use libr2fa::FixedTimeSource;
+    /// use libr2fa::SteamKey;
+    ///
+    /// let steam_key = SteamKey::from_shared_secret("test", "1Yl+tt/6w2dZEG51M8P6oc2x/cY=").unwrap();
+    ///
+    /// assert_eq!(steam_key.get_code_from(&FixedTimeSource(1_700_000_000)).unwrap(), "W5V7J");
+    /// assert!(steam_key.get_code_from(&FixedTimeSource(-1)).is_err());
+    /// ```
+    pub fn get_code_from(&self, time: &dyn TimeSource) -> Result<String, Error> {
+        self.get_code_at(time.now_unix())
+    }
+
+    /// generate the code for the given unix time in seconds
+    fn get_code_at(&self, unix: i64) -> Result<String, Error> {
+        // a time before the epoch would wrap around to a huge step
+        if unix < 0 {
+            return Err(Error::InvalidKey);
+        }
+
+        Ok(self.token.generate_code(unix as u64))
+    }
+
+    /// verify a steam code at the given time,
+    /// accepting the codes of up to `skew_steps` 30 second steps before and after it
+    ///
+    /// time is unix epoch in second,
+    /// the code is uppercased and stripped of whitespace as users often type it so,
+    /// the codes are compared in constant time
+    ///
+    /// ```rust
+    /// use libr2fa::SteamKey;
+    ///
+    /// let steam_key = SteamKey::from_shared_secret("test", "1Yl+tt/6w2dZEG51M8P6oc2x/cY=").unwrap();
+    ///
+    /// assert!(steam_key.verify_at("W5V7J", 1_700_000_000, 0));
+    /// assert!(steam_key.verify_at("W5V7J", 1_700_000_030, 1));
+    /// assert!(!steam_key.verify_at("W5V7J", 1_700_000_030, 0));
+    /// assert!(steam_key.verify_at("w5v 7j", 1_700_000_000, 0));
+    /// ```
+    pub fn verify_at(&self, code: &str, time: u64, skew_steps: u8) -> bool {
+        let code: String = code
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+
+        let step = time / 30u64;
+        let first = step.saturating_sub(skew_steps as u64);
+        let last = step.saturating_add(skew_steps as u64);
+
+        let mut matched = false;
+        for step in first..=last {
+            let expected = self.token.generate_code_for_step(step);
+            // check every step, so the time taken does not depend on which one matched
+            matched |= crate::util::constant_time_eq(&expected, &code);
+        }
+
+        matched
+    }
+
+    /// get the uri in the format used by the steam mobile app and SDA,
+    /// `otpauth://totp/Steam:NAME?secret=SECRET&issuer=Steam&digits=5&encoder=steam`
+    ///
+    /// third party authenticators recognise the steam alphabet
+    /// from the issuer and digits,
+    /// `otpauth_from_uri` recognises it from the `encoder` and gives a `SteamKey` back
+    ///
+    /// ```rust
+    /// use libr2fa::SteamKey;
+    /// use libr2fa::steam::MaFile;
+    ///
+    /// let mafile = MaFile::from_file("./public/mafile_test.mafile").unwrap();
+    /// let steam_key = SteamKey::from_mafile(mafile).unwrap();
+    ///
+    /// assert_eq!(
+    ///     steam_key.to_compat_uri(),
+    ///     "otpauth://totp/Steam:test?secret=2WEX5NW77LBWOWIQNZ2THQ72UHG3D7OG&issuer=Steam&digits=5&encoder=steam"
+    /// );
+    /// ```
+    pub fn to_compat_uri(&self) -> String {
+        let name = url::form_urlencoded::byte_serialize(self.mafile.account_name.as_bytes())
+            .collect::<String>();
+
+        format!(
+            "otpauth://totp/Steam:{}?secret={}&issuer=Steam&digits=5&encoder=steam",
+            name,
+            self.token.to_base32()
+        )
+    }
+}
+
+impl TryFrom<&MaFile> for SteamKey {
+    type Error = Error;
+
+    fn try_from(value: &MaFile) -> Result<Self, Self::Error> {
+        SteamKey::from_mafile_ref(value)
+    }
 }
 
 impl Key for SteamKey {
     fn get_code(&mut self) -> Result<String, crate::error::Error> {
-        // get unix epoch in seconds
-        let time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        self.get_code_from(&SystemTimeSource)
+    }
 
-        let res = self.token.generate_code(time);
+    fn provisioning_uri(&self) -> Result<String, Error> {
+        Ok(self.to_uri_struct().to_string())
+    }
 
-        Ok(res)
+    fn sort_key(&self) -> (String, String) {
+        ("Steam".to_string(), self.mafile.account_name.clone())
     }
 
     fn get_name(&self) -> &str {
@@ -67,10 +270,42 @@ impl Key for SteamKey {
         crate::KeyType::Steam
     }
 
+    fn get_digits(&self) -> u8 {
+        5
+    }
+
+    fn get_algorithm(&self) -> Option<crate::HMACType> {
+        None
+    }
+
+    fn get_interval(&self) -> Option<u64> {
+        Some(30)
+    }
+
+    fn get_counter(&self) -> Option<u64> {
+        None
+    }
+
+    fn get_code_at_time(&mut self, unix_seconds: i64) -> Result<String, Error> {
+        self.get_code_at(unix_seconds)
+    }
+
+    fn step_progress_from(&self, time: &dyn TimeSource) -> Option<f64> {
+        crate::util::step_progress(time.now_unix(), 0, 30)
+    }
+
+    fn security_advice(&self) -> Vec<String> {
+        vec![]
+    }
+
     fn set_name(&mut self, name: &str) {
         self.mafile.account_name = name.to_string();
     }
 
+    fn set_counter(&mut self, _counter: u64) {
+        // steam is time based and has no counter
+    }
+
     fn set_recovery_codes(&mut self, recovery_codes: Vec<String>) {
         if recovery_codes.is_empty() {
             return;
@@ -78,9 +313,21 @@ impl Key for SteamKey {
         self.mafile.revocation_code = recovery_codes[0].clone();
     }
 
+    fn regenerate_recovery_codes(&mut self, _count: usize) {
+        // the revocation code is issued by steam and can not be generated locally
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Key> {
+        Box::new(self.clone())
+    }
 }
 
 impl OtpAuthKey for SteamKey {
@@ -94,6 +341,9 @@ impl OtpAuthKey for SteamKey {
             counter: None,
             period: None,
             issuer: Some(String::from("Steam")),
+            epoch: None,
+            encoder: Some(String::from("steam")),
+            image: None,
         }
     }
 
@@ -29,3 +29,372 @@ fn test_steam_two_factor_secret_parse() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+fn test_steam_two_factor_secret_deserialize() {
+    let token = super::token::TwoFactorSecret::parse_shared_secret(
+        "1Yl+tt/6w2dZEG51M8P6oc2x/cY=".to_string(),
+    )
+    .unwrap();
+
+    let res: Result<super::token::TwoFactorSecret, _> =
+        serde_json::from_str("\"1Yl+tt/6w2dZEG51M8P6oc2x/cY=\"");
+    assert_eq!(res.unwrap(), token);
+
+    // base32 fallback
+    let res: Result<super::token::TwoFactorSecret, _> =
+        serde_json::from_str(&format!("\"{}\"", token.to_base32()));
+    assert_eq!(res.unwrap(), token);
+
+    // malformed secret
+    let res: Result<super::token::TwoFactorSecret, _> = serde_json::from_str("\"not a secret\"");
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_steam_generate_device_id() {
+    let re = regex::Regex::new(
+        r"^android:[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$",
+    )
+    .unwrap();
+
+    let device_id = super::generate_device_id();
+    assert!(re.is_match(&device_id), "{}", device_id);
+    assert_ne!(device_id, super::generate_device_id());
+}
+
+#[test]
+fn test_steam_generate_code_for_step() -> Result<(), Error> {
+    let token = super::token::TwoFactorSecret::parse_shared_secret(
+        "1Yl+tt/6w2dZEG51M8P6oc2x/cY=".to_string(),
+    )?;
+
+    let time = 1_700_000_000;
+    assert_eq!(
+        token.generate_code(time),
+        token.generate_code_for_step(time / 30)
+    );
+    assert_eq!(
+        token.generate_code(time + 29 - time % 30),
+        token.generate_code_for_step(time / 30)
+    );
+    assert_ne!(
+        token.generate_code_for_step(time / 30),
+        token.generate_code_for_step(time / 30 + 1)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_steam_generate_code_fixed() -> Result<(), Error> {
+    let token = super::token::TwoFactorSecret::parse_shared_secret(
+        "1Yl+tt/6w2dZEG51M8P6oc2x/cY=".to_string(),
+    )?;
+
+    // computed with an independent python version of the reference algorithm
+    // of node-steam-totp and SteamDesktopAuthenticator:
+    // hmac-sha1 of the big endian 30 second step, rfc 4226 dynamic truncation,
+    // then 5 characters of the alphabet taken least significant first
+    let vectors = [
+        (0, "GCJXH"),
+        (29, "GCJXH"),
+        (30, "WMRBQ"),
+        (1_000_000_000, "7JXXX"),
+        (1_700_000_000, "W5V7J"),
+        // past the 32 bit range
+        (4_294_967_296, "C9M8T"),
+    ];
+    for (time, code) in vectors {
+        assert_eq!(token.generate_code(time), code, "time {}", time);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_steam_key_accessors() -> Result<(), Error> {
+    let mafile = crate::steam::MaFile::from_file("./public/mafile_test.mafile")?;
+    let mut steam_key: Box<dyn Key> = Box::new(crate::SteamKey::from_mafile(mafile)?);
+
+    assert_eq!(steam_key.get_digits(), 5);
+    assert_eq!(steam_key.get_algorithm(), None);
+    assert_eq!(steam_key.get_interval(), Some(30));
+
+    steam_key.set_counter(7);
+    assert_eq!(steam_key.get_counter(), None);
+    assert!(steam_key.is_time_based());
+    assert_eq!(
+        steam_key.provisioning_uri()?,
+        "otpauth://totp/Steam:test?secret=2WEX5NW77LBWOWIQNZ2THQ72UHG3D7OG&issuer=Steam&encoder=steam"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_steam_encoder_uri() -> Result<(), Error> {
+    let uri = "otpauth://totp/Steam:test?secret=2WEX5NW77LBWOWIQNZ2THQ72UHG3D7OG&issuer=Steam&encoder=steam";
+
+    let uri_struct = crate::URI::from(uri);
+    assert_eq!(uri_struct.key_type, crate::KeyType::Steam);
+    assert_eq!(uri_struct.encoder, Some("steam".to_string()));
+
+    let mut key = crate::otpauth_from_uri(uri)?;
+    assert_eq!(key.get_type(), crate::KeyType::Steam);
+
+    let mafile = crate::steam::MaFile::from_file("./public/mafile_test.mafile")?;
+    let mut steam_key = crate::SteamKey::from_mafile(mafile)?;
+    let code = key.get_code()?;
+    assert_eq!(code.len(), 5);
+    assert_eq!(code, steam_key.get_code()?);
+
+    // round trip through the uri string
+    let uri = crate::OtpAuthKey::get_uri(&steam_key);
+    assert!(uri.contains("encoder=steam"));
+    let mut key = crate::otpauth_from_uri(&uri)?;
+    assert_eq!(key.get_type(), crate::KeyType::Steam);
+    assert_eq!(key.get_code()?, steam_key.get_code()?);
+
+    Ok(())
+}
+
+#[test]
+fn test_steam_compat_uri_round_trip() -> Result<(), Error> {
+    let mafile = crate::steam::MaFile::from_file("./public/mafile_test.mafile")?;
+    let mut steam_key = crate::SteamKey::from_mafile(mafile)?;
+
+    // the `encoder` makes it a steam key, though the uri is a totp one
+    let mut key = crate::otpauth_from_uri(&steam_key.to_compat_uri())?;
+    assert_eq!(key.get_type(), crate::KeyType::Steam);
+    assert_eq!(key.get_name(), "test");
+
+    let imported = key.as_any().downcast_ref::<crate::SteamKey>();
+    assert!(imported.is_some());
+    assert_eq!(
+        imported.unwrap().token.generate_code(1_700_000_000),
+        steam_key.token.generate_code(1_700_000_000)
+    );
+    assert_eq!(key.get_code()?, steam_key.get_code()?);
+
+    Ok(())
+}
+
+#[test]
+fn test_steam_label_hotp_uri() -> Result<(), Error> {
+    // only the steam encoder of a totp uri makes a steam key, not the label
+    let uri = "otpauth://hotp/Steam:alice?secret=2WEX5NW77LBWOWIQNZ2THQ72UHG3D7OG&counter=5";
+    let key = crate::otpauth_from_uri(uri)?;
+    assert_eq!(key.get_type(), crate::KeyType::HOTP);
+    assert_eq!(key.get_counter(), Some(5));
+
+    let uri = format!("{}&encoder=steam", uri);
+    let key = crate::otpauth_from_uri(&uri)?;
+    assert_eq!(key.get_type(), crate::KeyType::HOTP);
+    assert_eq!(key.get_counter(), Some(5));
+
+    Ok(())
+}
+
+#[test]
+fn test_steam_uri_special_characters() {
+    let uri = crate::URI {
+        name: "john doe?&#=+".to_string(),
+        key_type: crate::KeyType::Steam,
+        secret: "2WEX5NW77LBWOWIQNZ2THQ72UHG3D7OG".to_string(),
+        ..Default::default()
+    };
+
+    for s in [uri.to_string(), uri.to_percent_encoded_uri()] {
+        let parsed: crate::URI = s.parse().unwrap();
+        assert_eq!(parsed.key_type, crate::KeyType::Steam);
+        assert_eq!(parsed.name, uri.name);
+        assert_eq!(parsed.secret, uri.secret);
+    }
+    assert!(uri
+        .to_percent_encoded_uri()
+        .starts_with("otpauth://totp/Steam:john%20doe%3F%26%23%3D%2B?"));
+}
+
+#[test]
+fn test_steam_generate_code_with() -> Result<(), Error> {
+    let token = super::token::TwoFactorSecret::parse_shared_secret(
+        "1Yl+tt/6w2dZEG51M8P6oc2x/cY=".to_string(),
+    )?;
+
+    let time = 1_700_000_000;
+    let code = token.generate_code(time);
+    assert_eq!(code.len(), super::STEAM_CODE_LENGTH);
+    assert!(code
+        .bytes()
+        .all(|c| super::STEAM_CODE_ALPHABET.contains(&c)));
+    assert_eq!(
+        code,
+        token.generate_code_with(time, super::STEAM_CODE_LENGTH, super::STEAM_CODE_ALPHABET)
+    );
+
+    let code = token.generate_code_with(time, 8, b"0123456789");
+    assert_eq!(code.len(), 8);
+    assert!(code.bytes().all(|c| c.is_ascii_digit()));
+
+    assert_eq!(token.generate_code_with(time, 5, b""), "");
+
+    Ok(())
+}
+
+#[test]
+fn test_mafile_missing_optional_fields() -> Result<(), Error> {
+    let mafile = crate::steam::MaFile::from_file("./public/mafile_minimal_test.mafile")?;
+
+    assert_eq!(mafile.account_name, "test");
+    assert_eq!(mafile.secret_1, "");
+    assert_eq!(mafile.token_gid, "");
+    assert_eq!(mafile.serial_number, 0);
+
+    let steam_key = crate::SteamKey::from_mafile(mafile)?;
+    assert_eq!(steam_key.token.generate_code(1_700_000_000), "W5V7J");
+
+    let mafile = crate::steam::MaFile::from_string(
+        r#"{
+            "account_name": "test",
+            "shared_secret": "1Yl+tt/6w2dZEG51M8P6oc2x/cY=",
+            "serial_number": "18446744073709551615",
+            "server_time": 1700000000
+        }"#,
+    )?;
+    assert_eq!(mafile.serial_number, u64::MAX);
+    assert_eq!(mafile.server_time, 1_700_000_000);
+
+    let mafile = crate::steam::MaFile::from_string(
+        r#"{"account_name": "test", "shared_secret": "1Yl+tt/6w2dZEG51M8P6oc2x/cY=", "serial_number": "abc"}"#,
+    );
+    assert!(mafile.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_steam_key_from_shared_secret() -> Result<(), Error> {
+    let steam_key = crate::SteamKey::from_shared_secret("test", "1Yl+tt/6w2dZEG51M8P6oc2x/cY=")?;
+    assert_eq!(steam_key.token.generate_code(1_700_000_000), "W5V7J");
+    assert!(steam_key.matches_issuer("steam"));
+
+    assert_eq!(steam_key.validate(), Ok(()));
+
+    let mut key: Box<dyn Key> = Box::new(steam_key.clone());
+    assert_eq!(key.get_code_at_time(1_700_000_000)?, "W5V7J");
+    assert_eq!(key.get_code_at_time(-1), Err(Error::InvalidKey));
+    assert!(!steam_key.matches_issuer("test"));
+
+    let res = crate::SteamKey::from_shared_secret("test", "");
+    assert_eq!(res.err(), Some(Error::InvalidKey));
+
+    Ok(())
+}
+
+#[test]
+fn test_steam_key_verify_at() -> Result<(), Error> {
+    let steam_key = crate::SteamKey::from_shared_secret("test", "1Yl+tt/6w2dZEG51M8P6oc2x/cY=")?;
+
+    assert!(steam_key.verify_at("W5V7J", 1_700_000_000, 0));
+    assert!(!steam_key.verify_at("W5V7J", 1_700_000_000 + 60, 1));
+    assert!(steam_key.verify_at("W5V7J", 1_700_000_000 - 30, 1));
+    // submitted codes are uppercased and stripped of whitespace
+    assert!(steam_key.verify_at("w5v7j", 1_700_000_000, 0));
+    assert!(steam_key.verify_at(" W5V 7J\n", 1_700_000_000, 0));
+
+    // the window does not underflow at the start of the epoch
+    assert!(steam_key.verify_at("GCJXH", 0, 2));
+
+    Ok(())
+}
+
+#[test]
+fn test_steam_two_factor_secret_base32_length() {
+    // 10 bytes
+    let res = super::token::TwoFactorSecret::from_base32("GEZDGNBVGY3TQOJQ".to_string());
+    assert_eq!(
+        res.err(),
+        Some(Error::InvalidBase32(
+            "steam secret decoded to 10 bytes, expected 20".to_string()
+        ))
+    );
+
+    let res = super::token::TwoFactorSecret::from_base32("not base32!".to_string());
+    assert!(matches!(res, Err(Error::InvalidBase32(_))));
+
+    let res =
+        super::token::TwoFactorSecret::from_base32("2WEX5NW77LBWOWIQNZ2THQ72UHG3D7OG".to_string());
+    assert!(res.is_ok());
+}
+
+#[test]
+fn test_steam_key_default() -> Result<(), Error> {
+    let mut steam_key = crate::SteamKey::default();
+    assert_eq!(steam_key.get_name(), "");
+    assert!(steam_key.security_advice().is_empty());
+    assert_eq!(
+        steam_key.step_progress_from(&crate::FixedTimeSource(1_699_999_995)),
+        Some(0.5)
+    );
+
+    let code = steam_key.get_code()?;
+    assert_eq!(code.len(), 5);
+    assert_eq!(
+        steam_key.token.generate_code(1_700_000_000),
+        crate::SteamKey::default()
+            .token
+            .generate_code(1_700_000_000)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_steam_key_validate() -> Result<(), Error> {
+    let mafile = crate::steam::MaFile::from_file("./public/mafile_test.mafile")?;
+    let mut steam_key = crate::SteamKey::from_mafile(mafile)?;
+    assert_eq!(steam_key.validate(), Ok(()));
+
+    // the token no longer matches the mafile
+    steam_key.token = super::token::TwoFactorSecret::new();
+    assert_eq!(steam_key.validate(), Err(Error::InvalidKey));
+
+    // a truncated shared secret, which is not base32 either
+    steam_key.mafile.shared_secret = "1Yl+tt/6w2dZEG51".to_string();
+    assert!(matches!(steam_key.validate(), Err(Error::InvalidBase32(_))));
+
+    // some sources store the shared secret base32 encoded
+    let mafile = crate::steam::MaFile::from_file("./public/mafile_test.mafile")?;
+    let mut steam_key = crate::SteamKey::from_mafile(mafile)?;
+    steam_key.mafile.shared_secret = steam_key.token.to_base32();
+    assert_eq!(steam_key.validate(), Ok(()));
+
+    Ok(())
+}
+
+#[test]
+fn test_steam_key_serde_round_trip() -> Result<(), Error> {
+    let mafile = crate::steam::MaFile::from_file("./public/mafile_test.mafile")?;
+    let steam_key = crate::SteamKey::from_mafile(mafile)?;
+
+    let json = serde_json::to_string(&steam_key).unwrap();
+    let loaded: crate::SteamKey = serde_json::from_str(&json).unwrap();
+
+    // the token is serialized as its base64 shared secret
+    assert_eq!(
+        loaded.token.to_shared_secret(),
+        steam_key.token.to_shared_secret()
+    );
+    assert_eq!(
+        serde_json::to_value(&loaded.mafile).unwrap(),
+        serde_json::to_value(&steam_key.mafile).unwrap()
+    );
+    assert_eq!(
+        loaded.token.generate_code(1_700_000_000),
+        steam_key.token.generate_code(1_700_000_000)
+    );
+    assert_eq!(loaded.validate(), Ok(()));
+
+    Ok(())
+}
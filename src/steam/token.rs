@@ -1,6 +1,13 @@
-use hmac::{Hmac, Mac};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::HMACType;
+
+/// the length of a steam guard code
+pub const STEAM_CODE_LENGTH: usize = 5;
+
+/// the characters of a steam guard code
+pub const STEAM_CODE_ALPHABET: &[u8] = b"23456789BCDFGHJKMNPQRTVWXY";
+
 #[derive(Debug, Clone)]
 pub struct TwoFactorSecret([u8; 20]);
 
@@ -20,19 +27,27 @@ impl TwoFactorSecret {
     }
 
     /// parse the base32 token to self data type
+    ///
+    /// returns `Error::InvalidKey` if the secret is empty,
+    /// `Error::InvalidBase32` if it is not valid base32
+    /// or does not decode to 20 bytes, which usually means it is truncated
     pub fn from_base32(secret: String) -> Result<Self, crate::error::Error> {
         // ensure!(secret.len() != 0, "unable to parse empty shared secret");
         if secret.is_empty() {
             return Err(crate::error::Error::InvalidKey);
         }
         let res = data_encoding::BASE32.decode(secret.as_bytes());
-        if res.is_err() {
-            return Err(crate::error::Error::InvalidKey);
+        if let Err(e) = res {
+            return Err(crate::error::Error::InvalidBase32(e.to_string()));
         }
         let res = res.unwrap();
+        let len = res.len();
         let res: Result<[u8; 20], _> = res.try_into();
         if res.is_err() {
-            return Err(crate::error::Error::InvalidKey);
+            return Err(crate::error::Error::InvalidBase32(format!(
+                "steam secret decoded to {} bytes, expected 20",
+                len
+            )));
         }
         let res = res.unwrap();
 
@@ -59,61 +74,50 @@ impl TwoFactorSecret {
         Ok(Self(res))
     }
 
+    /// parse the token as stored, the shared secret is base64 encoded,
+    /// but some sources store it base32 encoded
+    ///
+    /// returns the error of `from_base32` if it is neither
+    pub(crate) fn parse_stored_secret(secret: String) -> Result<Self, crate::error::Error> {
+        match TwoFactorSecret::parse_shared_secret(secret.clone()) {
+            Ok(token) => Ok(token),
+            Err(_) => TwoFactorSecret::from_base32(secret),
+        }
+    }
+
     /// Generate a 5 character 2FA code to that can be used to log in to Steam.
     ///
     /// time is unix epoch in second
     pub fn generate_code(&self, time: u64) -> String {
-        let steam_guard_code_translations: [u8; 26] = [
-            50, 51, 52, 53, 54, 55, 56, 57, 66, 67, 68, 70, 71, 72, 74, 75, 77, 78, 80, 81, 82, 84,
-            86, 87, 88, 89,
-        ];
-
         // this effectively makes it so that it creates a new code every 30 seconds.
-        let time_bytes: [u8; 8] = build_time_bytes(time / 30u64);
-        // let hashed_data = hmacsha1::hmac_sha1(self.0.expose_secret(), &time_bytes);
-        // let hashed_data = ring::hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY;
-        // let signer = ring::hmac::Key::new(hashed_data, &self.0);
-        // let hashed_data = ring::hmac::sign(&signer, &time_bytes);
-        // let hashed_data = hashed_data.as_ref();
-
-        let mut mac = Hmac::<sha1::Sha1>::new_from_slice(&self.0).unwrap();
-        mac.update(&time_bytes);
-        let result = mac.finalize();
-        let hashed_data: &[u8] = &result.into_bytes();
-
-        let mut code_array: [u8; 5] = [0; 5];
-        let b = (hashed_data[19] & 0xF) as usize;
-        let mut code_point: i32 = ((hashed_data[b] & 0x7F) as i32) << 24
-            | (hashed_data[b + 1] as i32) << 16
-            | (hashed_data[b + 2] as i32) << 8
-            | (hashed_data[b + 3] as i32);
-
-        // for i in 0..5 {
-        // code_array[i] = steam_guard_code_translations
-        // [code_point as usize % steam_guard_code_translations.len()];
-        // code_point /= steam_guard_code_translations.len() as i32;
-        // }
-
-        code_array[0] = steam_guard_code_translations
-            [code_point as usize % steam_guard_code_translations.len()];
-        code_point /= steam_guard_code_translations.len() as i32;
-
-        code_array[1] = steam_guard_code_translations
-            [code_point as usize % steam_guard_code_translations.len()];
-        code_point /= steam_guard_code_translations.len() as i32;
-
-        code_array[2] = steam_guard_code_translations
-            [code_point as usize % steam_guard_code_translations.len()];
-        code_point /= steam_guard_code_translations.len() as i32;
-
-        code_array[3] = steam_guard_code_translations
-            [code_point as usize % steam_guard_code_translations.len()];
-        code_point /= steam_guard_code_translations.len() as i32;
-
-        code_array[4] = steam_guard_code_translations
-            [code_point as usize % steam_guard_code_translations.len()];
-
-        String::from_utf8(code_array.to_vec()).unwrap()
+        self.generate_code_for_step(time / 30u64)
+    }
+
+    /// Generate the 5 character 2FA code for the given time step.
+    ///
+    /// the step is the number of 30 second intervals since unix epoch for steam,
+    /// but any step counter can be given, e.g. for other time-sync strategies
+    pub fn generate_code_for_step(&self, step: u64) -> String {
+        self.generate_code_for_step_with(step, STEAM_CODE_LENGTH, STEAM_CODE_ALPHABET)
+    }
+
+    /// Generate a code of `length` characters from `alphabet`,
+    /// using the same base-N truncation as steam.
+    ///
+    /// time is unix epoch in second, a new code is created every 30 seconds,
+    /// `generate_code` is this with `STEAM_CODE_LENGTH` and `STEAM_CODE_ALPHABET`
+    ///
+    /// returns an empty string if the alphabet is empty
+    pub fn generate_code_with(&self, time: u64, length: usize, alphabet: &[u8]) -> String {
+        self.generate_code_for_step_with(time / 30u64, length, alphabet)
+    }
+
+    fn generate_code_for_step_with(&self, step: u64, length: usize, alphabet: &[u8]) -> String {
+        let time_bytes: [u8; 8] = build_time_bytes(step);
+        // hmac accepts keys of any length, so this never fails for the 20 byte secret
+        let hashed_data = HMACType::SHA1.get_hash(&self.0, &time_bytes).unwrap();
+
+        crate::util::truncate_to_alphabet(&hashed_data, length, alphabet)
     }
 }
 
@@ -141,7 +145,9 @@ impl<'de> Deserialize<'de> for TwoFactorSecret {
     where
         D: Deserializer<'de>,
     {
-        Ok(TwoFactorSecret::parse_shared_secret(String::deserialize(deserializer)?).unwrap())
+        let secret = String::deserialize(deserializer)?;
+
+        TwoFactorSecret::parse_stored_secret(secret).map_err(serde::de::Error::custom)
     }
 }
 
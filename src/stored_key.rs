@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{error, HOTPKey, Key, TOTPKey};
+
+/// a key of any type, which can be serialized
+///
+/// `Box<dyn Key>` is a trait object and can not be serialized,
+/// convert a reference to it to a `StoredKey` with `try_from` to store it,
+/// and back to a `Box<dyn Key>` with `into` after loading it
+///
+/// the key type is stored in the `type` field, in lowercase as in `KeyType`
+///
+/// ```rust
+/// use libr2fa::HOTPKey;
+/// use libr2fa::Key;
+/// use libr2fa::KeyType;
+/// use libr2fa::StoredKey;
+///
+/// let key: Box<dyn Key> = Box::new(HOTPKey {
+///     key: "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=".to_string(),
+///     ..Default::default()
+/// });
+///
+/// let stored = StoredKey::try_from(key.as_ref()).unwrap();
+/// assert!(matches!(stored, StoredKey::HOTP(_)));
+///
+/// let key: Box<dyn Key> = stored.into();
+/// assert_eq!(key.get_type(), KeyType::HOTP);
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum StoredKey {
+    /// HOTP key
+    HOTP(HOTPKey),
+    /// TOTP key
+    TOTP(TOTPKey),
+    /// steam guard key
+    #[cfg(feature = "steam")]
+    Steam(crate::SteamKey),
+}
+
+impl StoredKey {
+    /// the stored keys whose issuer is `issuer`, ignoring case, in their order in `keys`,
+    /// see `Key::matches_issuer`
+    ///
+    /// ```rust
+    /// use libr2fa::HOTPKey;
+    /// use libr2fa::StoredKey;
+    /// use libr2fa::TOTPKey;
+    ///
+    /// let keys = vec![
+    ///     StoredKey::TOTP(TOTPKey {
+    ///         name: "a".to_string(),
+    ///         issuer: Some("ACME Co".to_string()),
+    ///         ..Default::default()
+    ///     }),
+    ///     StoredKey::HOTP(HOTPKey {
+    ///         name: "b".to_string(),
+    ///         ..Default::default()
+    ///     }),
+    ///     StoredKey::HOTP(HOTPKey {
+    ///         name: "c".to_string(),
+    ///         issuer: Some("acme co".to_string()),
+    ///         ..Default::default()
+    ///     }),
+    /// ];
+    ///
+    /// let found = StoredKey::find_by_issuer(&keys, "ACME CO");
+    /// assert_eq!(found.len(), 2);
+    /// assert!(matches!(found[0], StoredKey::TOTP(key) if key.name == "a"));
+    /// assert!(matches!(found[1], StoredKey::HOTP(key) if key.name == "c"));
+    ///
+    /// assert!(StoredKey::find_by_issuer(&keys, "other").is_empty());
+    /// ```
+    pub fn find_by_issuer<'a>(keys: &'a [StoredKey], issuer: &str) -> Vec<&'a StoredKey> {
+        keys.iter()
+            .filter(|key| key.as_key().matches_issuer(issuer))
+            .collect()
+    }
+
+    /// the stored key as a `Key`, without converting it to a `Box<dyn Key>`
+    fn as_key(&self) -> &dyn Key {
+        match self {
+            StoredKey::HOTP(key) => key,
+            StoredKey::TOTP(key) => key,
+            #[cfg(feature = "steam")]
+            StoredKey::Steam(key) => key,
+        }
+    }
+}
+
+/// the key is cloned, so it can still be used after storing it,
+/// returns `Error::InvalidKey` if the key is not one of the key types of this crate
+impl TryFrom<&dyn Key> for StoredKey {
+    type Error = error::Error;
+
+    fn try_from(value: &dyn Key) -> Result<Self, Self::Error> {
+        let any = value.as_any();
+
+        if let Some(key) = any.downcast_ref::<HOTPKey>() {
+            return Ok(StoredKey::HOTP(key.clone()));
+        }
+        if let Some(key) = any.downcast_ref::<TOTPKey>() {
+            return Ok(StoredKey::TOTP(key.clone()));
+        }
+        #[cfg(feature = "steam")]
+        if let Some(key) = any.downcast_ref::<crate::SteamKey>() {
+            return Ok(StoredKey::Steam(key.clone()));
+        }
+
+        Err(error::Error::InvalidKey)
+    }
+}
+
+impl From<StoredKey> for Box<dyn Key> {
+    fn from(value: StoredKey) -> Self {
+        match value {
+            StoredKey::HOTP(key) => Box::new(key),
+            StoredKey::TOTP(key) => Box::new(key),
+            #[cfg(feature = "steam")]
+            StoredKey::Steam(key) => Box::new(key),
+        }
+    }
+}
@@ -73,11 +73,12 @@ fn totp_sha1_work() {
         6,
         1,
         30,
-        totp_rs::Secret::Encoded(
-            "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=".to_string(),
-        )
-        .to_bytes()
-        .unwrap(),
+        data_encoding::BASE32
+            .decode(
+                "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A="
+                    .as_bytes(),
+            )
+            .unwrap(),
     );
     if let Err(err) = totp_key2 {
         panic!("{}", err);
@@ -103,11 +104,12 @@ fn totp_sha256_work() {
         6,
         1,
         30,
-        totp_rs::Secret::Encoded(
-            "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=".to_string(),
-        )
-        .to_bytes()
-        .unwrap(),
+        data_encoding::BASE32
+            .decode(
+                "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A="
+                    .as_bytes(),
+            )
+            .unwrap(),
     );
     if let Err(err) = totp_key2 {
         panic!("{}", err);
@@ -133,11 +135,12 @@ fn totp_sha512_work() {
         6,
         1,
         30,
-        totp_rs::Secret::Encoded(
-            "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=".to_string(),
-        )
-        .to_bytes()
-        .unwrap(),
+        data_encoding::BASE32
+            .decode(
+                "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A="
+                    .as_bytes(),
+            )
+            .unwrap(),
     );
     if let Err(err) = totp_key2 {
         panic!("{}", err);
@@ -159,7 +162,7 @@ fn uri_decoder_totp_work() {
     let mut totp_key1 = totp_key1.unwrap();
 
     let mut totp_key2 = crate::TOTPKey {
-        name: "ACME Co:john.doe@email.com".to_string(),
+        name: "john.doe@email.com".to_string(),
         issuer: Some("ACME Co".to_string()),
         key: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
         digits: 7,
@@ -182,7 +185,7 @@ fn uri_decoder_hotp_work() {
     let mut hotp_key1 = hotp_key1.unwrap();
 
     let mut hotp_key2 = crate::HOTPKey {
-        name: "ACME Co:john.doe@email.com".to_string(),
+        name: "john.doe@email.com".to_string(),
         issuer: Some("ACME Co".to_string()),
         key: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
         digits: 7,
@@ -196,6 +199,7 @@ fn uri_decoder_hotp_work() {
     assert_eq!(hotp_key1.get_code(), hotp_key2.get_code());
 }
 
+#[cfg(feature = "qrcode")]
 #[test]
 fn uri_qrcode_decoder_totp_work() {
     let totp_key1 = crate::otpauth_from_uri_qrcode("public/uri_qrcode_test.png");
@@ -205,7 +209,7 @@ fn uri_qrcode_decoder_totp_work() {
     let mut totp_key1 = totp_key1.unwrap();
 
     let mut totp_key2 = crate::TOTPKey {
-        name: "ACME Co:john.doe@email.com".to_string(),
+        name: "john.doe@email.com".to_string(),
         issuer: Some("ACME Co".to_string()),
         key: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
         digits: 7,
@@ -219,6 +223,7 @@ fn uri_qrcode_decoder_totp_work() {
     assert_eq!(totp_key1.get_code(), totp_key2.get_code());
 }
 
+#[cfg(feature = "qrcode")]
 #[test]
 fn uri_qrcode_encoder_work() {
     let uri = crate::URI::new_from_uri("otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co&algorithm=SHA256&digits=7&period=60".to_string());
@@ -232,7 +237,7 @@ fn uri_qrcode_encoder_work() {
     let mut totp_key1 = totp_key1.unwrap();
 
     let mut totp_key2 = crate::TOTPKey {
-        name: "ACME Co:john.doe@email.com".to_string(),
+        name: "john.doe@email.com".to_string(),
         issuer: Some("ACME Co".to_string()),
         key: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
         digits: 7,
@@ -245,3 +250,1370 @@ fn uri_qrcode_encoder_work() {
     assert_eq!(totp_key1.get_type(), totp_key2.get_type());
     assert_eq!(totp_key1.get_code(), totp_key2.get_code());
 }
+
+#[test]
+fn totp_matched_window_work() {
+    let secret = "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=";
+    let totp_key1 = crate::TOTPKey {
+        key: secret.to_string(),
+        hmac_type: crate::HMACType::SHA1,
+        ..Default::default()
+    };
+
+    let totp_key2 = totp_rs::TOTP::new(
+        totp_rs::Algorithm::SHA1,
+        6,
+        1,
+        30,
+        data_encoding::BASE32.decode(secret.as_bytes()).unwrap(),
+    )
+    .unwrap();
+
+    let unix = 1_700_000_000;
+    // code from the previous time step
+    let code = totp_key2.generate(unix as u64 - 30);
+
+    let step = totp_key1.matched_window(&code, unix, 1);
+    assert_eq!(step, Some(unix / 30 - 1));
+    assert_eq!(totp_key1.matched_window(&code, unix, 0), None);
+
+    // the caller stores the last used step and rejects a reuse of it
+    let last_used_step = step.unwrap();
+    let replay = totp_key1.matched_window(&code, unix + 5, 1);
+    assert!(replay.is_some_and(|step| step <= last_used_step));
+}
+
+#[test]
+fn totp_verify_detailed_work() {
+    let secret = "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=";
+    let totp_key1 = crate::TOTPKey {
+        key: secret.to_string(),
+        hmac_type: crate::HMACType::SHA1,
+        ..Default::default()
+    };
+
+    let totp_key2 = totp_rs::TOTP::new(
+        totp_rs::Algorithm::SHA1,
+        6,
+        1,
+        30,
+        data_encoding::BASE32.decode(secret.as_bytes()).unwrap(),
+    )
+    .unwrap();
+
+    let unix = 1_700_000_000;
+    // code from the previous time step
+    let code = totp_key2.generate(unix as u64 - 30);
+
+    let res = totp_key1.verify_detailed(&code, unix, 1, 0);
+    assert!(res.matched);
+    assert_eq!(res.step, Some(unix / 30 - 1));
+    assert_eq!(res.drift_seconds, Some(-30));
+
+    let res = totp_key1.verify_detailed(&code, unix, 0, 1);
+    assert_eq!(res, crate::VerifyResult::default());
+}
+
+#[test]
+fn totp_downcast_work() {
+    let totp_key = crate::TOTPKey {
+        key: "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=".to_string(),
+        ..Default::default()
+    };
+
+    let mut key: Box<dyn Key> = Box::new(totp_key.clone());
+
+    assert_eq!(
+        key.as_any().downcast_ref::<crate::TOTPKey>(),
+        Some(&totp_key)
+    );
+    assert!(key.as_any().downcast_ref::<HOTPKey>().is_none());
+
+    key.as_any_mut()
+        .downcast_mut::<crate::TOTPKey>()
+        .unwrap()
+        .time_step = 60;
+    assert_eq!(
+        key.as_any()
+            .downcast_ref::<crate::TOTPKey>()
+            .unwrap()
+            .time_step,
+        60
+    );
+}
+
+#[test]
+fn totp_builder_work() {
+    let totp_key = crate::TOTPKey::builder()
+        .secret("HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ")
+        .name("john.doe@email.com")
+        .issuer("ACME Co")
+        .t0(10)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        totp_key,
+        crate::TOTPKey {
+            name: "john.doe@email.com".to_string(),
+            key: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
+            issuer: Some("ACME Co".to_string()),
+            t0: 10,
+            ..Default::default()
+        }
+    );
+
+    for digits in [0, 5, 9, 20] {
+        let res = crate::TOTPKey::builder()
+            .secret("HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ")
+            .digits(digits)
+            .build();
+        assert_eq!(res, Err(crate::Error::InvalidDigits));
+    }
+
+    let res = crate::TOTPKey::builder().build();
+    assert_eq!(res, Err(crate::Error::InvalidKey));
+
+    let res = crate::TOTPKey::builder().secret("not base32!").build();
+    assert_eq!(res, Err(crate::Error::InvalidKey));
+
+    // strict mode rejects short secrets, 1 byte then 20 bytes
+    let res = crate::TOTPKey::builder().secret("GE").strict().build();
+    assert_eq!(res, Err(crate::Error::InvalidKey));
+    let res = crate::TOTPKey::builder()
+        .secret("HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ")
+        .strict()
+        .build();
+    assert!(res.is_ok());
+    let res = crate::TOTPKey::builder()
+        .secret("HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ")
+        .min_secret_len(21)
+        .build();
+    assert_eq!(res, Err(crate::Error::InvalidKey));
+}
+
+#[test]
+fn totp_builder_secret_encoding_work() {
+    use crate::SecretEncoding;
+
+    // the rfc 6238 secret, "12345678901234567890"
+    let secrets = [
+        (SecretEncoding::Base32, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"),
+        (SecretEncoding::Base64, "MTIzNDU2Nzg5MDEyMzQ1Njc4OTA="),
+        (
+            SecretEncoding::Hex,
+            "3132333435363738393031323334353637383930",
+        ),
+    ];
+    for (encoding, secret) in secrets {
+        let totp_key = crate::TOTPKey::builder()
+            .secret(secret)
+            .secret_encoding(encoding)
+            .digits(8)
+            .build()
+            .unwrap();
+        assert_eq!(totp_key.key, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+        assert_eq!(totp_key.get_code_at(59).unwrap(), "94287082");
+    }
+
+    let res = crate::TOTPKey::builder()
+        .secret("not base64!")
+        .secret_encoding(SecretEncoding::Base64)
+        .build();
+    assert_eq!(res, Err(crate::Error::InvalidKey));
+}
+
+#[test]
+fn hotp_counter_overflow_work() {
+    let mut hotp_key = HOTPKey {
+        key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+        counter: u64::MAX - 1,
+        ..Default::default()
+    };
+
+    assert!(hotp_key.get_code().is_ok());
+    assert_eq!(hotp_key.counter, u64::MAX);
+
+    // the counter is exhausted, and left unchanged
+    assert_eq!(hotp_key.get_code(), Err(crate::Error::InvalidKey));
+    assert_eq!(hotp_key.get_code_value(), Err(crate::Error::InvalidKey));
+    assert_eq!(hotp_key.verify("287082"), Err(crate::Error::InvalidKey));
+    assert_eq!(
+        hotp_key.get_code_and_persist(|_| Ok(())),
+        Err(crate::Error::InvalidKey)
+    );
+    assert_eq!(hotp_key.counter, u64::MAX);
+}
+
+#[test]
+fn hotp_verify_work() {
+    let mut hotp_key = HOTPKey {
+        key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+        counter: 2,
+        ..Default::default()
+    };
+
+    // rfc 4226 test vectors for the counters 3 and 4, the old code is rejected
+    assert_eq!(hotp_key.verify("359152"), Ok(false));
+    assert_eq!(hotp_key.verify("338314"), Ok(false));
+    assert_eq!(hotp_key.counter, 2);
+    assert_eq!(hotp_key.verify("969429"), Ok(true));
+    assert_eq!(hotp_key.counter, 3);
+
+    // the verified counter is the one get_code uses next
+    assert_eq!(hotp_key.verify("338314"), Ok(true));
+    assert_eq!(hotp_key.get_code(), Ok("254676".to_string()));
+
+    hotp_key.digits = 5;
+    assert_eq!(hotp_key.verify("287922"), Err(crate::Error::InvalidDigits));
+    assert_eq!(hotp_key.counter, 5);
+}
+
+#[test]
+fn hotp_digits_work() {
+    let mut hotp_key1 = HOTPKey {
+        key: "MZZHI6LHOVUGU===".to_string(),
+        counter: 4,
+        digits: 8,
+        ..Default::default()
+    };
+
+    let hotp_key2 =
+        libauthenticator::hotp("MZZHI6LHOVUGU===", 5, libauthenticator::Algorithm::SHA1, 8)
+            .unwrap();
+
+    assert_eq!(hotp_key1.get_code().unwrap(), format!("{:08}", hotp_key2));
+
+    hotp_key1.digits = 20;
+    assert_eq!(hotp_key1.get_code(), Err(crate::Error::InvalidDigits));
+    // the counter is not consumed by a rejected call
+    assert_eq!(hotp_key1.counter, 5);
+}
+
+#[test]
+fn totp_digits_work() {
+    let secret = "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=";
+    let mut totp_key1 = crate::TOTPKey {
+        key: secret.to_string(),
+        digits: 8,
+        ..Default::default()
+    };
+
+    let totp_key2 = totp_rs::TOTP::new(
+        totp_rs::Algorithm::SHA1,
+        8,
+        1,
+        30,
+        data_encoding::BASE32.decode(secret.as_bytes()).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        totp_key1.get_code().unwrap(),
+        totp_key2.generate_current().unwrap()
+    );
+
+    totp_key1.digits = 20;
+    assert_eq!(totp_key1.get_code(), Err(crate::Error::InvalidDigits));
+}
+
+#[test]
+fn constant_time_eq_work() {
+    assert!(crate::util::constant_time_eq("123456", "123456"));
+    assert!(crate::util::constant_time_eq("", ""));
+    assert!(!crate::util::constant_time_eq("123456", "123457"));
+    assert!(!crate::util::constant_time_eq("123456", "023456"));
+    assert!(!crate::util::constant_time_eq("123456", "1234567"));
+}
+
+#[test]
+fn totp_codes_in_window_work() {
+    let secret = "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=";
+    let totp_key1 = crate::TOTPKey {
+        key: secret.to_string(),
+        ..Default::default()
+    };
+
+    let totp_key2 = totp_rs::TOTP::new(
+        totp_rs::Algorithm::SHA1,
+        6,
+        1,
+        30,
+        data_encoding::BASE32.decode(secret.as_bytes()).unwrap(),
+    )
+    .unwrap();
+
+    let unix = 1_700_000_000;
+    let codes = totp_key1.codes_in_window(unix, 2, 1).unwrap();
+    assert_eq!(codes.len(), 4);
+    for (i, (time, code)) in codes.iter().enumerate() {
+        assert_eq!(*time, (unix / 30 - 2 + i as i64) * 30);
+        assert_eq!(*code, totp_key2.generate(*time as u64));
+    }
+
+    // steps before the epoch are skipped
+    let codes = totp_key1.codes_in_window(0, 2, 1).unwrap();
+    assert_eq!(codes.len(), 2);
+    assert_eq!(codes[0].0, 0);
+}
+
+#[test]
+fn uri_epoch_work() {
+    use crate::OtpAuthKey;
+
+    let totp_key1 = crate::TOTPKey {
+        key: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
+        t0: 1_000,
+        ..Default::default()
+    };
+
+    let uri = totp_key1.get_uri();
+    assert!(uri.contains("epoch=1000"));
+    let totp_key2: crate::TOTPKey = uri.parse().unwrap();
+    assert_eq!(totp_key2.t0, 1_000);
+
+    let totp_key2: crate::TOTPKey =
+        "otpauth://totp/test?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&t0=-30"
+            .parse()
+            .unwrap();
+    assert_eq!(totp_key2.t0, -30);
+
+    // absent epoch keeps the default
+    let totp_key1 = crate::TOTPKey {
+        key: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
+        ..Default::default()
+    };
+    let uri = totp_key1.get_uri();
+    assert!(!uri.contains("epoch"));
+    let totp_key2: crate::TOTPKey = uri.parse().unwrap();
+    assert_eq!(totp_key2.t0, 0);
+}
+
+#[test]
+fn generate_recovery_codes_work() {
+    let codes = crate::generate_recovery_codes(20, 5);
+    assert_eq!(codes.len(), 20);
+
+    let re =
+        regex::Regex::new(r"^[2-9a-hjkmnp-z]{5}-[2-9a-hjkmnp-z]{5}-[2-9a-hjkmnp-z]{5}$").unwrap();
+    for code in codes.iter() {
+        assert!(re.is_match(code), "{}", code);
+    }
+
+    let mut deduped = codes.clone();
+    deduped.sort();
+    deduped.dedup();
+    assert_eq!(deduped.len(), codes.len());
+
+    assert!(crate::generate_recovery_codes(0, 4).is_empty());
+}
+
+#[test]
+fn key_type_round_trip_work() {
+    use crate::KeyType;
+
+    let key_types = [
+        KeyType::HOTP,
+        KeyType::TOTP,
+        #[cfg(feature = "steam")]
+        KeyType::Steam,
+    ];
+
+    for key_type in key_types {
+        let s = key_type.to_string();
+        assert_eq!(s, s.to_ascii_lowercase());
+        assert_eq!(KeyType::from(s.as_str()), key_type);
+        assert_eq!(KeyType::from(s.to_ascii_uppercase()), key_type);
+    }
+
+    #[cfg(feature = "steam")]
+    {
+        assert_eq!(KeyType::from(KeyType::Steam.to_string()), KeyType::Steam);
+
+        let json = serde_json::to_string(&KeyType::Steam).unwrap();
+        assert_eq!(json, "\"steam\"");
+        let key_type: KeyType = serde_json::from_str(&json).unwrap();
+        assert_eq!(key_type, KeyType::Steam);
+        let key_type: KeyType = serde_json::from_str("\"Steam\"").unwrap();
+        assert_eq!(key_type, KeyType::Steam);
+        let key_type: KeyType = serde_json::from_str("\"HOTP\"").unwrap();
+        assert_eq!(key_type, KeyType::HOTP);
+    }
+}
+
+#[test]
+fn hotp_code_value_work() {
+    let mut hotp_key1 = HOTPKey {
+        key: "MZZHI6LHOVUGU===".to_string(),
+        counter: 4,
+        ..Default::default()
+    };
+    let mut hotp_key2 = hotp_key1.clone();
+
+    let value = libauthenticator::hotp("MZZHI6LHOVUGU===", 5, libauthenticator::Algorithm::SHA1, 6)
+        .unwrap();
+
+    assert_eq!(hotp_key1.get_code_value().unwrap(), value);
+    assert_eq!(hotp_key2.get_code().unwrap(), format!("{:06}", value));
+}
+
+#[test]
+fn uri_param_order_work() {
+    use std::collections::HashSet;
+
+    let uri1 = crate::URI::from("otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co&algorithm=SHA256&digits=7&period=60");
+    let uri2 = crate::URI::from("otpauth://totp/ACME%20Co:john.doe@email.com?period=60&digits=7&algorithm=SHA256&issuer=ACME%20Co&secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ");
+
+    assert_eq!(uri1, uri2);
+    assert_eq!(uri1.to_string(), uri2.to_string());
+    assert_eq!(crate::URI::from(uri1.to_string()), uri1);
+
+    let mut set = HashSet::new();
+    set.insert(uri1);
+    assert!(set.contains(&uri2));
+}
+
+#[test]
+fn uri_missing_digits_work() {
+    use crate::OtpAuthKey;
+
+    let uri = "otpauth://hotp/john.doe@email.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=0";
+    assert_eq!(crate::URI::from(uri).digits, None);
+
+    // absent digits default to 6
+    let mut hotp_key: HOTPKey = uri.parse().unwrap();
+    assert_eq!(hotp_key.digits, 6);
+    assert_eq!(hotp_key.get_code(), Ok("287082".to_string()));
+
+    let totp_key: crate::TOTPKey =
+        "otpauth://totp/john.doe@email.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"
+            .parse()
+            .unwrap();
+    assert_eq!(totp_key.digits, 6);
+    assert_eq!(totp_key.to_uri_struct().digits, Some(6));
+
+    // explicit zero digits are rejected by the fallible parser
+    let res = format!("{}&digits=0", uri).parse::<HOTPKey>();
+    assert!(matches!(res, Err(crate::Error::InvalidURI(_))));
+}
+
+#[test]
+fn uri_invalid_digits_work() {
+    let uri = "otpauth://totp/john.doe@email.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    for (digits, clamped) in [
+        ("abc", None),
+        ("255", Some(8)),
+        ("0", Some(6)),
+        ("5", Some(6)),
+    ] {
+        let s = format!("{}&digits={}", uri, digits);
+        assert_eq!(
+            s.parse::<crate::URI>(),
+            Err(crate::Error::InvalidURI(format!(
+                "invalid digits {}, expected 6, 7 or 8",
+                digits
+            )))
+        );
+        assert!(crate::URI::from_str_lenient(&s).is_err());
+
+        // the infallible parser clamps them, or ignores them if not a number
+        assert_eq!(crate::URI::from(s).digits, clamped);
+    }
+
+    for digits in [6, 7, 8] {
+        let s = format!("{}&digits={}", uri, digits);
+        assert_eq!(s.parse::<crate::URI>().unwrap().digits, Some(digits));
+    }
+
+    // steam ignores the digits
+    #[cfg(feature = "steam")]
+    assert!(
+        "otpauth://totp/Steam:test?secret=2WEX5NW77LBWOWIQNZ2THQ72UHG3D7OG&issuer=Steam&digits=5&encoder=steam"
+            .parse::<crate::URI>()
+            .is_ok()
+    );
+
+    // a totp account whose issuer is steam, without the steam encoder, keeps its digits
+    let uri =
+        "otpauth://totp/Steam:test?secret=2WEX5NW77LBWOWIQNZ2THQ72UHG3D7OG&issuer=Steam&digits=6"
+            .parse::<crate::URI>()
+            .unwrap();
+    assert_eq!(uri.key_type, crate::KeyType::TOTP);
+    assert_eq!(uri.digits, Some(6));
+    assert!(
+        "otpauth://totp/Steam:test?secret=2WEX5NW77LBWOWIQNZ2THQ72UHG3D7OG&issuer=Steam&digits=5"
+            .parse::<crate::URI>()
+            .is_err()
+    );
+}
+
+#[test]
+fn uri_missing_secret_work() {
+    let uri = "otpauth://totp/ACME%20Co:john.doe@email.com?issuer=ACME%20Co&digits=7";
+    let missing = crate::Error::InvalidURI("missing secret".to_string());
+
+    assert_eq!(uri.parse::<crate::URI>(), Err(missing.clone()));
+    assert_eq!(uri.parse::<crate::TOTPKey>(), Err(missing.clone()));
+    assert!(matches!(crate::otpauth_from_uri(uri), Err(e) if e == missing));
+
+    let uri = "otpauth://hotp/ACME%20Co:john.doe@email.com?secret=&counter=1";
+    assert_eq!(uri.parse::<crate::HOTPKey>(), Err(missing));
+}
+
+#[test]
+fn uri_image_work() {
+    let uri = "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&algorithm=SHA1&digits=6&issuer=ACME%20Co&image=https%3A%2F%2Fexample.com%2Flogo.png%3Fsize%3D64%26theme%3Ddark";
+    let uri = crate::URI::from(uri);
+
+    assert_eq!(
+        uri.image,
+        Some("https://example.com/logo.png?size=64&theme=dark".to_string())
+    );
+
+    let s = uri.to_string();
+    assert!(s.contains("&image=https%3A%2F%2Fexample.com%2Flogo.png%3Fsize%3D64%26theme%3Ddark"));
+    assert_eq!(crate::URI::from(s), uri);
+
+    let uri = crate::URI {
+        image: Some("https://example.com/a b+c.png".to_string()),
+        ..uri
+    };
+    assert_eq!(crate::URI::from(uri.to_string()), uri);
+}
+
+#[test]
+fn secret_work() {
+    use crate::Secret;
+
+    let raw = b"12345678901234567890".to_vec();
+    let encoded = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string();
+
+    assert_eq!(Secret::Encoded(encoded.clone()).to_bytes(), Ok(raw.clone()));
+    assert_eq!(Secret::Raw(raw.clone()).to_encoded(), Ok(encoded.clone()));
+    assert_eq!(
+        Secret::Encoded("not base32!".to_string()).to_bytes(),
+        Err(crate::Error::InvalidKey)
+    );
+
+    let mut from_raw = crate::TOTPKey::from_secret(Secret::Raw(raw.clone())).unwrap();
+    let mut from_encoded = crate::TOTPKey::from_secret(Secret::Encoded(encoded)).unwrap();
+    assert_eq!(from_raw.get_code(), from_encoded.get_code());
+
+    // rfc 4226 test vector for counter 1
+    let mut hotp_key = HOTPKey::from_secret(raw.into()).unwrap();
+    assert_eq!(hotp_key.get_code(), Ok("287082".to_string()));
+
+    assert!(HOTPKey::from_secret(Secret::Raw(vec![])).is_err());
+}
+
+#[test]
+fn secret_hex_work() {
+    use crate::Secret;
+
+    let hex = "3132333435363738393031323334353637383930";
+    assert_eq!(
+        Secret::Hex(hex.to_string()).to_bytes(),
+        Ok(b"12345678901234567890".to_vec())
+    );
+    assert_eq!(
+        Secret::Hex("4A4b".to_string()).to_bytes(),
+        Ok(vec![0x4a, 0x4b])
+    );
+
+    // odd length and not hex
+    assert_eq!(
+        Secret::Hex("313".to_string()).to_bytes(),
+        Err(crate::Error::InvalidKey)
+    );
+    assert!(HOTPKey::from_secret(Secret::Hex("not hex!".to_string())).is_err());
+
+    // rfc 4226 test vectors for the counters 1 to 9
+    let hotp_key = HOTPKey::from_secret(Secret::Hex(hex.to_string())).unwrap();
+    let codes = hotp_key.preview_codes(9).unwrap();
+    let expected = [
+        "287082", "359152", "969429", "338314", "254676", "287922", "162583", "399871", "520489",
+    ];
+    for ((counter, code), expected) in codes.into_iter().zip(expected) {
+        assert_eq!(code, expected, "counter {}", counter);
+    }
+}
+
+#[test]
+fn hotp_preview_codes_overflow_work() {
+    let hotp_key = HOTPKey {
+        key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+        counter: u64::MAX - 2,
+        ..Default::default()
+    };
+
+    // the preview stops at the last counter instead of repeating it
+    let counters = hotp_key
+        .preview_codes(5)
+        .unwrap()
+        .into_iter()
+        .map(|(counter, _)| counter)
+        .collect::<Vec<u64>>();
+    assert_eq!(counters, vec![u64::MAX - 1, u64::MAX]);
+
+    let hotp_key = HOTPKey {
+        counter: u64::MAX,
+        ..hotp_key
+    };
+    assert!(hotp_key.preview_codes(5).unwrap().is_empty());
+}
+
+#[test]
+fn generate_secret_work() {
+    let a = crate::generate_secret(crate::DEFAULT_SECRET_LENGTH);
+    let b = crate::generate_secret(crate::DEFAULT_SECRET_LENGTH);
+    assert_ne!(a, b);
+
+    for len in [10, 16, 20, 32, 64] {
+        let secret = crate::Secret::Encoded(crate::generate_secret(len));
+        assert_eq!(secret.to_bytes().unwrap().len(), len);
+    }
+
+    let key = crate::TOTPKey::new_random("john.doe@email.com", "ACME Co");
+    assert_eq!(key.name, "john.doe@email.com");
+    assert_eq!(key.digits, 6);
+    assert_eq!(key.time_step, 30);
+    assert!(crate::TOTPKey::builder().secret(&key.key).build().is_ok());
+}
+
+#[test]
+fn uri_label_issuer_work() {
+    // issuer from the label prefix
+    let uri = crate::URI::from(
+        "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ",
+    );
+    assert_eq!(uri.name, "john.doe@email.com");
+    assert_eq!(uri.issuer, Some("ACME Co".to_string()));
+
+    // url-encoded colon and a space after it
+    let uri = crate::URI::from(
+        "otpauth://totp/ACME%20Co%3A%20john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ",
+    );
+    assert_eq!(uri.name, "john.doe@email.com");
+    assert_eq!(uri.issuer, Some("ACME Co".to_string()));
+
+    // the issuer parameter takes precedence over the label prefix
+    let uri = crate::URI::from(
+        "otpauth://totp/Old%20Name:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co",
+    );
+    assert_eq!(uri.name, "john.doe@email.com");
+    assert_eq!(uri.issuer, Some("ACME Co".to_string()));
+
+    // no prefix
+    let uri = crate::URI::from(
+        "otpauth://totp/john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ",
+    );
+    assert_eq!(uri.name, "john.doe@email.com");
+    assert_eq!(uri.issuer, None);
+    assert!(uri
+        .to_string()
+        .starts_with("otpauth://totp/john.doe%40email.com?"));
+
+    let uri = crate::URI::from(
+        "otpauth://totp/john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co",
+    );
+    assert_eq!(uri.name, "john.doe@email.com");
+    assert_eq!(uri.issuer, Some("ACME Co".to_string()));
+    assert!(uri
+        .to_string()
+        .starts_with("otpauth://totp/ACME+Co%3Ajohn.doe%40email.com?"));
+    let parsed = crate::URI::from(uri.to_string());
+    assert_eq!(parsed.name, uri.name);
+    assert_eq!(parsed.issuer, uri.issuer);
+}
+
+#[test]
+fn totp_display_code_work() {
+    let mut totp_key = crate::TOTPKey {
+        key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+        ..Default::default()
+    };
+
+    let displayed = totp_key.display_code().to_string();
+    assert_eq!(displayed.len(), 6);
+    assert!(totp_key
+        .matched_window(&displayed, crate::util::now_unix(), 1)
+        .is_some());
+    assert!(totp_key.get_code().is_ok());
+
+    let invalid = crate::TOTPKey {
+        key: "not base32!".to_string(),
+        ..Default::default()
+    };
+    assert!(invalid.display_code().to_string().starts_with('<'));
+}
+
+#[cfg(feature = "steam")]
+#[test]
+fn key_serde_round_trip_work() {
+    let mut hotp_key = HOTPKey {
+        name: "john.doe@email.com".to_string(),
+        key: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
+        digits: 8,
+        counter: 7,
+        recovery_codes: vec!["a".to_string(), "b".to_string()],
+        hmac_type: crate::HMACType::SHA256,
+        issuer: Some("ACME Co".to_string()),
+    };
+    let json = serde_json::to_string(&hotp_key).unwrap();
+    let mut loaded: HOTPKey = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded, hotp_key);
+    assert_eq!(loaded.get_code(), hotp_key.get_code());
+
+    let totp_key = crate::TOTPKey {
+        name: "john.doe@email.com".to_string(),
+        key: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
+        digits: 7,
+        time_step: 60,
+        t0: 10,
+        recovery_codes: vec!["a".to_string()],
+        hmac_type: crate::HMACType::SHA512,
+        issuer: Some("ACME Co".to_string()),
+    };
+    let json = serde_json::to_string(&totp_key).unwrap();
+    let loaded: crate::TOTPKey = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded, totp_key);
+    assert_eq!(loaded.get_code_at(59), totp_key.get_code_at(59));
+}
+
+#[cfg(feature = "steam")]
+#[test]
+fn stored_key_serde_work() {
+    use crate::StoredKey;
+
+    let mafile = crate::steam::MaFile::from_file("./public/mafile_test.mafile").unwrap();
+    let keys: Vec<Box<dyn Key>> = vec![
+        Box::new(HOTPKey {
+            name: "hotp".to_string(),
+            key: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
+            counter: 7,
+            ..Default::default()
+        }),
+        Box::new(crate::TOTPKey {
+            name: "totp".to_string(),
+            key: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
+            digits: 8,
+            ..Default::default()
+        }),
+        Box::new(crate::SteamKey::from_mafile(mafile).unwrap()),
+    ];
+
+    let stored = keys
+        .iter()
+        .map(|key| StoredKey::try_from(key.as_ref()))
+        .collect::<Result<Vec<StoredKey>, crate::Error>>()
+        .unwrap();
+    let json = serde_json::to_string(&stored).unwrap();
+    assert!(json.contains(r#""type":"hotp""#));
+    assert!(json.contains(r#""type":"totp""#));
+    assert!(json.contains(r#""type":"steam""#));
+
+    let steam = StoredKey::find_by_issuer(&stored, "steam");
+    assert_eq!(steam.len(), 1);
+    assert!(matches!(steam[0], StoredKey::Steam(_)));
+
+    let loaded: Vec<StoredKey> = serde_json::from_str(&json).unwrap();
+    let mut loaded: Vec<Box<dyn Key>> = loaded.into_iter().map(|key| key.into()).collect();
+    let mut keys = keys;
+
+    for (key, loaded) in keys.iter_mut().zip(loaded.iter_mut()) {
+        assert_eq!(key.get_type(), loaded.get_type());
+        assert_eq!(key.get_name(), loaded.get_name());
+        assert_eq!(key.get_counter(), loaded.get_counter());
+        assert_eq!(key.get_code(), loaded.get_code());
+    }
+}
+
+#[test]
+fn uri_label_special_characters_work() {
+    let uri = crate::URI {
+        name: "john: doe?/#&=+".to_string(),
+        issuer: Some("ACME: Co?".to_string()),
+        secret: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
+        ..Default::default()
+    };
+
+    for s in [uri.to_string(), uri.to_percent_encoded_uri()] {
+        let parsed: crate::URI = s.parse().unwrap();
+        assert_eq!(parsed.name, uri.name);
+        assert_eq!(parsed.issuer, uri.issuer);
+        assert_eq!(parsed.secret, uri.secret);
+    }
+    assert!(!uri.to_percent_encoded_uri().contains('+'));
+
+    // a colon in the name without an issuer is not taken as the separator
+    let uri = crate::URI {
+        name: "john:doe".to_string(),
+        secret: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
+        ..Default::default()
+    };
+    let parsed: crate::URI = uri.to_string().parse().unwrap();
+    assert_eq!(parsed.name, "john:doe");
+    assert_eq!(parsed.issuer, None);
+}
+
+#[test]
+fn uri_minimal_work() {
+    use crate::OtpAuthKey;
+
+    let mut totp_key = crate::TOTPKey {
+        name: "john.doe@email.com".to_string(),
+        issuer: Some("ACME Co".to_string()),
+        key: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
+        ..Default::default()
+    };
+
+    let minimal = totp_key.to_uri_struct().to_minimal_uri();
+    assert_eq!(
+        minimal,
+        "otpauth://totp/ACME+Co%3Ajohn.doe%40email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME+Co"
+    );
+    assert!(minimal.len() < totp_key.get_uri().len());
+
+    let mut parsed: crate::TOTPKey = minimal.parse().unwrap();
+    assert_eq!(parsed.get_code(), totp_key.get_code());
+
+    // the parameters which are not the defaults are kept
+    let uri = crate::URI {
+        algorithm: Some(crate::HMACType::SHA256),
+        digits: Some(8),
+        period: Some(60),
+        epoch: Some(100),
+        ..totp_key.to_uri_struct()
+    };
+    assert_eq!(
+        uri.to_minimal_uri(),
+        "otpauth://totp/ACME+Co%3Ajohn.doe%40email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&algorithm=SHA256&digits=8&period=60&epoch=100&issuer=ACME+Co"
+    );
+}
+
+#[test]
+fn uri_counter_period_exclusive_work() {
+    let totp = "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&period=30&counter=7";
+    assert_eq!(
+        totp.parse::<crate::URI>(),
+        Err(crate::Error::InvalidURI(
+            "counter is not allowed for totp".to_string()
+        ))
+    );
+    assert!(totp.parse::<crate::TOTPKey>().is_err());
+    let uri = crate::URI::from_str_lenient(totp).unwrap();
+    assert_eq!(uri.counter, Some(7));
+
+    let hotp = "otpauth://hotp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&period=30&counter=7";
+    assert_eq!(
+        hotp.parse::<crate::URI>(),
+        Err(crate::Error::InvalidURI(
+            "period is not allowed for hotp".to_string()
+        ))
+    );
+    let key = crate::otpauth_from_uri(hotp).unwrap();
+    assert_eq!(key.get_counter(), Some(7));
+    let uri = crate::URI::from_str_lenient(hotp).unwrap();
+    assert_eq!(uri.period, Some(30));
+
+    // the uris of the keys themselves are accepted
+    let hotp_key = HOTPKey {
+        key: "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".to_string(),
+        counter: 7,
+        ..Default::default()
+    };
+    let parsed: HOTPKey = crate::OtpAuthKey::get_uri(&hotp_key).parse().unwrap();
+    assert_eq!(parsed.counter, 7);
+}
+
+#[cfg(feature = "qrcoderead")]
+#[test]
+fn uri_qrcode_animated_work() {
+    let uris = crate::URI::from_qr_animated_gif("public/uri_qrcode_animated_test.gif").unwrap();
+
+    assert_eq!(uris.len(), 2);
+    assert_eq!(uris[0].secret, "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ");
+    assert_eq!(uris[1].secret, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+    assert_eq!(uris[1].issuer, Some("ACME Co".to_string()));
+
+    // not a gif
+    assert!(crate::URI::from_qr_animated_gif("public/uri_qrcode_test.png").is_err());
+    assert!(crate::URI::from_qr_animated_gif("public/not_exists.gif").is_err());
+}
+
+#[test]
+fn totp_zero_period_work() {
+    let uri = "otpauth://totp/x?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&period=0";
+    let invalid = crate::Error::InvalidURI("invalid period 0".to_string());
+    assert_eq!(uri.parse::<crate::URI>(), Err(invalid.clone()));
+    assert_eq!(crate::URI::from_str_lenient(uri), Err(invalid.clone()));
+    assert!(matches!(crate::otpauth_from_uri(uri), Err(e) if e == invalid));
+
+    // a key built without the checks errors instead of dividing by 0
+    let mut key = crate::key_from_uri_struct(&crate::URI::from(uri)).unwrap();
+    assert_eq!(key.get_code(), Err(crate::Error::InvalidKey));
+
+    let totp_key = crate::TOTPKey {
+        key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+        time_step: 0,
+        ..Default::default()
+    };
+    assert_eq!(totp_key.get_code_at(59), Err(crate::Error::InvalidKey));
+    assert_eq!(totp_key.valid_until(), Err(crate::Error::InvalidKey));
+    assert_eq!(
+        totp_key.codes_in_window(59, 1, 1),
+        Err(crate::Error::InvalidKey)
+    );
+    assert_eq!(totp_key.matched_window("287082", 59, 1), None);
+    assert!(!totp_key.verify_detailed("287082", 59, 1, 1).matched);
+}
+
+#[test]
+fn totp_before_t0_work() {
+    let totp_key = crate::TOTPKey {
+        key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+        t0: 100,
+        ..Default::default()
+    };
+
+    // a time before t0 must not wrap around to a huge counter
+    assert_eq!(totp_key.get_code_at(99), Err(crate::Error::InvalidKey));
+    assert_eq!(totp_key.get_code_at(-1), Err(crate::Error::InvalidKey));
+    assert_eq!(
+        totp_key.codes_in_window(99, 1, 1),
+        Err(crate::Error::InvalidKey)
+    );
+    assert!(totp_key.get_code_at(100).is_ok());
+    assert_eq!(totp_key.codes_in_window(100, 1, 1).unwrap().len(), 2);
+
+    let totp_key = crate::TOTPKey {
+        t0: i64::MAX,
+        ..totp_key
+    };
+    assert_eq!(totp_key.valid_until(), Err(crate::Error::InvalidKey));
+}
+
+#[test]
+fn totp_valid_until_work() {
+    let totp_key = crate::TOTPKey {
+        key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+        time_step: 60,
+        t0: 17,
+        ..Default::default()
+    };
+
+    let valid_until = totp_key.valid_until().unwrap();
+    let now = crate::util::now_unix();
+
+    assert_eq!((valid_until - 17) % 60, 0);
+    assert!(valid_until >= now);
+    assert!(valid_until <= now + 60);
+    // the code changes at the expiry
+    assert_ne!(
+        totp_key.get_code_at(valid_until),
+        totp_key.get_code_at(valid_until - 1)
+    );
+}
+
+#[cfg(feature = "qrcode")]
+#[test]
+fn uri_write_qr_png_work() {
+    let uri = crate::URI::new_from_uri(
+        "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co&algorithm=SHA256&digits=7&period=60".to_string(),
+    );
+
+    let mut png = vec![];
+    uri.write_qr_png(
+        &mut png,
+        crate::QrCodeOptions {
+            size: 600,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let img = image::load_from_memory(&png).unwrap();
+    assert_eq!(img.width(), 600);
+    assert_eq!(img.height(), 600);
+
+    let dir = std::env::temp_dir().join("r2fa_write_qr_png_test.png");
+    std::fs::write(&dir, &png).unwrap();
+    let read = crate::URI::from_qr_code(dir.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&dir).unwrap();
+    assert_eq!(read, uri);
+}
+
+#[cfg(feature = "qrcode")]
+#[test]
+fn otpauth_from_uri_qrcode_missing_secret_work() {
+    let uri = crate::URI::new_from_uri(
+        "otpauth://totp/ACME%20Co:john.doe@email.com?issuer=ACME%20Co&digits=7".to_string(),
+    );
+
+    let mut png = vec![];
+    uri.write_qr_png(&mut png, crate::QrCodeOptions::default())
+        .unwrap();
+
+    let dir = std::env::temp_dir().join("r2fa_qrcode_missing_secret_test.png");
+    std::fs::write(&dir, &png).unwrap();
+    let key = crate::otpauth_from_uri_qrcode(dir.to_str().unwrap());
+    std::fs::remove_file(&dir).unwrap();
+    assert!(matches!(key, Err(e) if e == crate::Error::InvalidURI("missing secret".to_string())));
+}
+
+#[test]
+fn dynamic_truncate_short_digest_work() {
+    use crate::util::dynamic_truncate;
+
+    // rfc 4226 section 5.4 example
+    let digest = [
+        0x1f, 0x86, 0x98, 0x69, 0x0e, 0x02, 0xca, 0x16, 0x61, 0x85, 0x50, 0xef, 0x7f, 0x19, 0xda,
+        0x8e, 0x94, 0x5b, 0x55, 0x5a,
+    ];
+    assert_eq!(dynamic_truncate(&digest), Ok(0x50ef7f19));
+
+    // too short to take 4 bytes from, must not panic
+    assert_eq!(dynamic_truncate(&[]), Err(crate::Error::InvalidKey));
+    assert_eq!(dynamic_truncate(&[0x0f; 3]), Err(crate::Error::InvalidKey));
+    assert!(dynamic_truncate(&[0x00; 4]).is_ok());
+
+    // the offset must not be past the end of the digest
+    assert!(dynamic_truncate(&[0x0c; 16]).is_ok());
+    assert_eq!(dynamic_truncate(&[0x0d; 16]), Err(crate::Error::InvalidKey));
+    assert_eq!(dynamic_truncate(&[0x0f; 4]), Err(crate::Error::InvalidKey));
+}
+
+#[test]
+fn dynamic_truncate_every_step_work() {
+    // every truncation offset is reached within a few hundred steps,
+    // each of them has to give a code for every algorithm
+    for hmac_type in [
+        crate::HMACType::SHA1,
+        crate::HMACType::SHA256,
+        crate::HMACType::SHA512,
+    ] {
+        let totp_key = crate::TOTPKey {
+            key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+            hmac_type,
+            ..Default::default()
+        };
+
+        for step in 0..1000 {
+            let code = totp_key.get_code_at(step * 30);
+            assert!(code.is_ok(), "{} step {}: {:?}", hmac_type, step, code);
+        }
+    }
+}
+
+#[test]
+fn same_account_work() {
+    let a = HOTPKey {
+        name: "john.doe@email.com".to_string(),
+        issuer: Some("ACME Co".to_string()),
+        key: "MFRGG===".to_string(),
+        counter: 1,
+        ..Default::default()
+    };
+
+    let b = HOTPKey {
+        counter: 100,
+        ..a.clone()
+    };
+    assert_ne!(a, b);
+    assert!(a.same_account(&b));
+
+    let c = HOTPKey {
+        issuer: Some("Other Co".to_string()),
+        ..a.clone()
+    };
+    assert!(!a.same_account(&c));
+
+    let d = HOTPKey {
+        hmac_type: crate::HMACType::SHA256,
+        ..a.clone()
+    };
+    assert!(!a.same_account(&d));
+
+    let e = HOTPKey {
+        key: "MFRGGZA=".to_string(),
+        ..a.clone()
+    };
+    assert!(!a.same_account(&e));
+}
+
+#[test]
+fn totp_verify_returning_step_work() {
+    let totp_key = crate::TOTPKey {
+        key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+        ..Default::default()
+    };
+
+    let now = crate::util::now_unix();
+    let previous = totp_key.get_code_at(now - 30).unwrap();
+    // a skew of 2, so the previous step is still in the window if the step changes meanwhile
+    let step = totp_key.verify_returning_step(&previous, 2).unwrap();
+    assert_eq!(step, Some(now / 30 - 1));
+
+    let invalid = crate::TOTPKey {
+        key: "not base32!".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(
+        invalid.verify_returning_step("123456", 1),
+        Err(crate::Error::InvalidKey)
+    );
+}
+
+#[test]
+fn base32_normalize_work() {
+    use crate::{is_valid_base32, normalize_base32};
+
+    // padded
+    assert!(is_valid_base32("MFRGGZA="));
+    assert_eq!(normalize_base32("MFRGGZA=").unwrap(), "MFRGGZA=");
+
+    // unpadded
+    assert!(!is_valid_base32("MFRGGZA"));
+    assert_eq!(normalize_base32("MFRGGZA").unwrap(), "MFRGGZA=");
+    assert_eq!(
+        normalize_base32("HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ").unwrap(),
+        "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ"
+    );
+
+    // whitespace and lowercase
+    assert!(!is_valid_base32("hxdm vjec jjws rb3h wizr 4ifu gftm xboz"));
+    assert_eq!(
+        normalize_base32(" hxdm vjec jjws rb3h\twizr 4ifu gftm xboz\n").unwrap(),
+        "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ"
+    );
+
+    // invalid characters and impossible lengths
+    assert!(matches!(
+        normalize_base32("MFRGG1"),
+        Err(crate::Error::InvalidBase32(_))
+    ));
+    assert!(normalize_base32("M").is_err());
+}
+
+#[test]
+fn uri_scheme_work() {
+    let expected = crate::URI::from(
+        "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co",
+    );
+    assert_eq!(expected.key_type, crate::KeyType::TOTP);
+
+    let uri = crate::URI::from(
+        "OTPAUTH://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co",
+    );
+    assert_eq!(uri, expected);
+
+    let uri = crate::URI::from(
+        "OtpAuth://TOTP/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co",
+    );
+    assert_eq!(uri, expected);
+
+    let uri = crate::URI::from(
+        "totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co",
+    );
+    assert_eq!(uri, expected);
+
+    let uri = crate::URI::from("hotp/Label?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&counter=3");
+    assert_eq!(uri.key_type, crate::KeyType::HOTP);
+    assert_eq!(uri.name, "Label");
+    assert_eq!(uri.counter, Some(3));
+}
+
+#[test]
+fn time_source_work() {
+    use crate::TimeSource;
+
+    struct MockClock(std::cell::Cell<i64>);
+
+    impl TimeSource for MockClock {
+        fn now_unix(&self) -> i64 {
+            let now = self.0.get();
+            self.0.set(now + 30);
+            now
+        }
+    }
+
+    let totp_key = crate::TOTPKey {
+        key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+        digits: 8,
+        ..Default::default()
+    };
+
+    // rfc 6238 test vectors, the clock moves a step each read
+    let clock = MockClock(std::cell::Cell::new(59));
+    assert_eq!(totp_key.get_code_from(&clock), Ok("94287082".to_string()));
+    assert_eq!(totp_key.get_code_from(&clock), Ok("37359152".to_string()));
+}
+
+#[test]
+fn step_progress_work() {
+    use crate::FixedTimeSource;
+
+    let totp_key = crate::TOTPKey {
+        time_step: 60,
+        t0: 10,
+        ..Default::default()
+    };
+
+    // the start, middle and end of the step from 70 to 130
+    assert_eq!(totp_key.step_progress_from(&FixedTimeSource(70)), Some(0.0));
+    assert_eq!(
+        totp_key.step_progress_from(&FixedTimeSource(100)),
+        Some(0.5)
+    );
+    let end = totp_key.step_progress_from(&FixedTimeSource(129)).unwrap();
+    assert!(end > 0.98 && end < 1.0);
+    assert_eq!(
+        totp_key.step_progress_from(&FixedTimeSource(130)),
+        Some(0.0)
+    );
+
+    // before t0
+    assert_eq!(
+        totp_key.step_progress_from(&FixedTimeSource(-20)),
+        Some(0.5)
+    );
+
+    let progress = totp_key.step_progress().unwrap();
+    assert!((0.0..1.0).contains(&progress));
+
+    let totp_key = crate::TOTPKey {
+        time_step: 0,
+        ..Default::default()
+    };
+    assert_eq!(totp_key.step_progress(), None);
+    assert_eq!(HOTPKey::default().step_progress(), None);
+}
+
+#[test]
+fn import_uri_list_work() {
+    let text = "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co
+
+otpauth://hotp/jane.doe@email.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=0
+  otpauth://totp/Steam:test?secret=2WEX5NW77LBWOWIQNZ2THQ72UHG3D7OG&issuer=Steam&encoder=steam  
+";
+
+    let mut keys = crate::import_uri_list(text).unwrap();
+    assert_eq!(keys.len(), 3);
+    assert_eq!(keys[0].get_name(), "john.doe@email.com");
+    assert_eq!(keys[1].get_code(), Ok("287082".to_string()));
+    assert_eq!(keys[2].get_name(), "test");
+
+    // the first failure is reported with its line number
+    let text = "# comment
+otpauth://totp/john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ
+
+otpauth://totp/jane.doe@email.com?counter=1
+otpauth://totp/jack.doe@email.com";
+    assert_eq!(
+        crate::import_uri_list(text).err(),
+        Some(crate::Error::InvalidURI(
+            "line 4: missing secret".to_string()
+        ))
+    );
+
+    assert!(crate::import_uri_list("").unwrap().is_empty());
+}
+
+#[test]
+fn get_code_at_time_work() {
+    let mut keys: Vec<Box<dyn Key>> = vec![
+        Box::new(HOTPKey {
+            key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+            ..Default::default()
+        }),
+        Box::new(crate::TOTPKey {
+            key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+            digits: 8,
+            ..Default::default()
+        }),
+    ];
+
+    // rfc 4226 and rfc 6238 test vectors, the time is ignored for HOTP
+    assert_eq!(keys[0].get_code_at_time(59), Ok("287082".to_string()));
+    assert_eq!(keys[0].get_code_at_time(59), Ok("359152".to_string()));
+    assert_eq!(keys[1].get_code_at_time(59), Ok("94287082".to_string()));
+    assert_eq!(
+        keys[1].get_code_at_time(1111111109),
+        Ok("07081804".to_string())
+    );
+}
+
+#[cfg(feature = "steam")]
+#[test]
+fn totp_code_with_alphabet_work() {
+    let steam_key =
+        crate::SteamKey::from_shared_secret("test", "1Yl+tt/6w2dZEG51M8P6oc2x/cY=").unwrap();
+    let totp_key = crate::TOTPKey {
+        key: steam_key.token.to_base32(),
+        ..Default::default()
+    };
+
+    // the steam scheme is the truncation with the steam length and alphabet over SHA1
+    let code = totp_key
+        .get_code_at_with(
+            1_700_000_000,
+            crate::steam::STEAM_CODE_LENGTH,
+            crate::steam::STEAM_CODE_ALPHABET,
+        )
+        .unwrap();
+    assert_eq!(code, "W5V7J");
+
+    assert_eq!(
+        totp_key.get_code_at_with(0, 5, b""),
+        Err(crate::Error::InvalidDigits)
+    );
+
+    // a time before t0 has no step counter, rather than wrapping around
+    let totp_key = crate::TOTPKey { t0: 10, ..totp_key };
+    assert_eq!(
+        totp_key.get_code_at_with(9, 5, b"ABC"),
+        Err(crate::Error::InvalidKey)
+    );
+    assert!(totp_key.get_code_at_with(10, 5, b"ABC").is_ok());
+
+    let invalid = crate::TOTPKey {
+        key: "not base32!".to_string(),
+        ..Default::default()
+    };
+    assert!(invalid.get_code_at_with(0, 5, b"ABC").is_err());
+}
+
+#[test]
+fn key_from_uri_struct_work() {
+    use crate::KeyType;
+
+    let uris = vec![
+        (
+            "otpauth://hotp/john.doe@email.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=0",
+            KeyType::HOTP,
+        ),
+        (
+            "otpauth://totp/john.doe@email.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ",
+            KeyType::TOTP,
+        ),
+        #[cfg(feature = "steam")]
+        (
+            "otpauth://totp/Steam:test?secret=2WEX5NW77LBWOWIQNZ2THQ72UHG3D7OG&issuer=Steam&encoder=steam",
+            KeyType::Steam,
+        ),
+    ];
+
+    for (uri, key_type) in uris {
+        let uri = crate::URI::from(uri);
+        let mut key = crate::key_from_uri_struct(&uri).unwrap();
+        assert_eq!(key.get_type(), key_type);
+        assert!(key.get_code().is_ok());
+    }
+
+    let mut key = crate::key_from_uri_struct(&crate::URI::from(
+        "otpauth://hotp/john.doe@email.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=0",
+    ))
+    .unwrap();
+    assert_eq!(key.get_code(), Ok("287082".to_string()));
+}
@@ -0,0 +1,43 @@
+/// a source of the current time for the time based keys
+///
+/// the keys read the system clock by default,
+/// a different source can be given to `TOTPKey::get_code_from`
+/// and `SteamKey::get_code_from`, e.g. a fixed time in tests
+///
+/// ```rust
+/// use libr2fa::FixedTimeSource;
+/// use libr2fa::TOTPKey;
+///
+/// let totp_key = TOTPKey {
+///     key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+///     digits: 8,
+///     ..Default::default()
+/// };
+///
+/// // rfc 6238 test vector
+/// assert_eq!(totp_key.get_code_from(&FixedTimeSource(59)).unwrap(), "94287082");
+/// ```
+pub trait TimeSource {
+    /// the current unix time in seconds
+    fn now_unix(&self) -> i64;
+}
+
+/// the system clock, the default time source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SystemTimeSource;
+
+impl TimeSource for SystemTimeSource {
+    fn now_unix(&self) -> i64 {
+        crate::util::now_unix()
+    }
+}
+
+/// a time source which is always the given unix time in seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FixedTimeSource(pub i64);
+
+impl TimeSource for FixedTimeSource {
+    fn now_unix(&self) -> i64 {
+        self.0
+    }
+}
@@ -1,8 +1,10 @@
-use std::rc::Rc;
+use std::{rc::Rc, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{error, HMACType, Key, OtpAuthKey};
+use crate::{
+    error, HMACType, Key, OtpAuthKey, Secret, SecretEncoding, SystemTimeSource, TimeSource,
+};
 
 /// TOTPKey is the key for the TOTP,
 /// TOTP is the time based key,
@@ -30,6 +32,8 @@ pub struct TOTPKey {
     pub key: String,
     /// digits
     /// 6, 7, 8
+    ///
+    /// `get_code` returns `Error::InvalidDigits` for any other value
     pub digits: u8,
     /// time step for the key
     pub time_step: u64,
@@ -59,18 +63,704 @@ impl Default for TOTPKey {
 }
 
 impl TOTPKey {
-    fn decode_key(&self) -> Result<Rc<[u8]>, error::Error> {
-        let key = data_encoding::BASE32.decode(self.get_key().as_bytes());
-        if key.is_err() {
+    /// create a key with the given secret and the default settings
+    ///
+    /// returns `Error::InvalidKey` if the secret is empty or not valid in its encoding
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    /// use libr2fa::Secret;
+    ///
+    /// let key = TOTPKey::from_secret(Secret::Raw(b"12345678901234567890".to_vec())).unwrap();
+    ///
+    /// assert_eq!(key.key, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+    /// ```
+    pub fn from_secret(secret: Secret) -> Result<Self, error::Error> {
+        let key = secret.to_encoded()?;
+        if key.is_empty() {
             return Err(error::Error::InvalidKey);
         }
 
-        Ok(Rc::from(key.unwrap().as_slice()))
+        Ok(Self {
+            key,
+            ..Default::default()
+        })
+    }
+
+    /// create a key with a newly generated secret and the default settings,
+    /// for enrolling a new account
+    ///
+    /// the secret is `DEFAULT_SECRET_LENGTH` random bytes,
+    /// see `generate_secret`
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    /// use libr2fa::Key;
+    ///
+    /// let mut key = TOTPKey::new_random("john.doe@email.com", "ACME Co");
+    ///
+    /// assert_eq!(key.issuer, Some("ACME Co".to_string()));
+    /// assert_eq!(key.get_code().unwrap().len(), 6);
+    /// ```
+    pub fn new_random(name: &str, issuer: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            key: crate::generate_secret(crate::DEFAULT_SECRET_LENGTH),
+            issuer: Some(issuer.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// whether the keys are for the same account,
+    /// comparing the secret, name, issuer and algorithm
+    ///
+    /// unlike `==`, this ignores the other settings and state,
+    /// such as the time step, t0 and the recovery codes,
+    /// e.g. to find duplicates when merging imported keys
+    ///
+    /// the secrets are compared decoded, so the same secret encoded differently still matches
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    ///
+    /// let a = TOTPKey {
+    ///     name: "john.doe@email.com".to_string(),
+    ///     key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let b = TOTPKey {
+    ///     digits: 8,
+    ///     recovery_codes: vec!["a2b3-c4d5-e6f7".to_string()],
+    ///     ..a.clone()
+    /// };
+    ///
+    /// assert_ne!(a, b);
+    /// assert!(a.same_account(&b));
+    /// ```
+    pub fn same_account(&self, other: &Self) -> bool {
+        let same_secret = match (self.decode_key(), other.decode_key()) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => self.key == other.key,
+        };
+
+        same_secret
+            && self.name == other.name
+            && self.issuer == other.issuer
+            && self.hmac_type == other.hmac_type
+    }
+
+    fn decode_key(&self) -> Result<Rc<[u8]>, error::Error> {
+        let key = Secret::Encoded(self.get_key().to_string()).to_bytes()?;
+
+        Ok(Rc::from(key.as_slice()))
     }
 
     fn get_key(&self) -> &str {
         &self.key
     }
+
+    /// build the key from the uri struct
+    fn from_uri(uri: &crate::URI) -> Self {
+        let time_step = uri.period.unwrap_or(30);
+        let digits = uri.digits.unwrap_or(6);
+        let algorithm = uri.algorithm.unwrap_or_default();
+
+        TOTPKey {
+            name: uri.name.clone(),
+            issuer: uri.issuer.clone(),
+            key: uri.secret.clone(),
+            digits,
+            time_step,
+            t0: uri.epoch.unwrap_or(0),
+            recovery_codes: Vec::default(),
+            hmac_type: algorithm,
+        }
+    }
+
+    /// get the time step counter for the given unix time
+    ///
+    /// returns `Error::InvalidKey` if the time step is 0,
+    /// or the time is before t0, whose negative step has no counter
+    fn get_step(&self, unix: i64) -> Result<i64, error::Error> {
+        if self.time_step == 0 || unix < self.t0 {
+            return Err(error::Error::InvalidKey);
+        }
+
+        Ok((unix - self.t0) / self.time_step as i64)
+    }
+
+    /// generate the code for the given time step counter
+    fn get_code_for_step(&self, step: i64) -> Result<String, error::Error> {
+        let code = self.get_code_value_for_step(step)?;
+
+        Ok(self.format_code(code))
+    }
+
+    /// generate the code value for the given time step counter
+    fn get_code_value_for_step(&self, step: i64) -> Result<u32, error::Error> {
+        if !(6..=8).contains(&self.digits) {
+            return Err(error::Error::InvalidDigits);
+        }
+        let raw = self.decode_key()?;
+        let c = step as u64;
+        let c = c.to_be_bytes();
+
+        let res = self.hmac_type.get_hash(raw.as_ref(), &c)?;
+        let code = crate::util::dynamic_truncate(&res)?;
+
+        // trim to the number of digits
+        Ok(code % 10u32.pow(self.digits as u32))
+    }
+
+    /// format the code value as a zero padded string of `digits` length
+    fn format_code(&self, code: u32) -> String {
+        let mut code = code.to_string();
+        // padding 0
+        while code.len() < self.digits as usize {
+            code.insert(0, '0');
+        }
+
+        code
+    }
+
+    /// get the current code as an integer,
+    /// the value before it is zero padded to `digits` length by `get_code`
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    /// use libr2fa::Key;
+    ///
+    /// let mut totp_key = TOTPKey {
+    ///     key: "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let value = totp_key.get_code_value().unwrap();
+    ///
+    /// assert!(value < 1_000_000);
+    /// ```
+    pub fn get_code_value(&mut self) -> Result<u32, error::Error> {
+        let step = self.get_step(crate::util::now_unix())?;
+
+        self.get_code_value_for_step(step)
+    }
+
+    /// generate the code for the given unix time
+    ///
+    /// unlike `Key::get_code`, this does not need `&mut self`
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    ///
+    /// let totp_key = TOTPKey {
+    ///     key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+    ///     digits: 8,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// // rfc 6238 test vector
+    /// assert_eq!(totp_key.get_code_at(59).unwrap(), "94287082");
+    ///
+    /// // there are no codes before t0
+    /// assert!(totp_key.get_code_at(-1).is_err());
+    /// ```
+    pub fn get_code_at(&self, unix: i64) -> Result<String, error::Error> {
+        self.get_code_for_step(self.get_step(unix)?)
+    }
+
+    /// generate a code of `length` characters from `alphabet` for the given unix time,
+    /// with the base-N truncation of steam guard codes rather than decimal digits,
+    /// for vendors which use a steam like scheme with their own length and alphabet
+    ///
+    /// the digits of the key are ignored,
+    /// returns `Error::InvalidDigits` if the alphabet is empty,
+    /// and `Error::InvalidKey` if the time is before the start time of the key
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    ///
+    /// let totp_key = TOTPKey {
+    ///     key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let code = totp_key.get_code_at_with(59, 8, b"ABCDEFGH").unwrap();
+    ///
+    /// assert_eq!(code.len(), 8);
+    /// assert!(code.bytes().all(|c| b"ABCDEFGH".contains(&c)));
+    ///
+    /// assert!(totp_key.get_code_at_with(59, 8, b"").is_err());
+    /// assert!(totp_key.get_code_at_with(-1, 8, b"ABCDEFGH").is_err());
+    /// ```
+    pub fn get_code_at_with(
+        &self,
+        unix: i64,
+        length: usize,
+        alphabet: &[u8],
+    ) -> Result<String, error::Error> {
+        if alphabet.is_empty() {
+            return Err(error::Error::InvalidDigits);
+        }
+        let raw = self.decode_key()?;
+        let step = self.get_step(unix)? as u64;
+
+        let res = self.hmac_type.get_hash(raw.as_ref(), &step.to_be_bytes())?;
+
+        Ok(crate::util::truncate_to_alphabet(&res, length, alphabet))
+    }
+
+    /// generate the code for the current time of the given time source
+    ///
+    /// `Key::get_code` is this with the system clock,
+    /// see `TimeSource`
+    pub fn get_code_from(&self, time: &dyn TimeSource) -> Result<String, error::Error> {
+        self.get_code_at(time.now_unix())
+    }
+
+    /// get the unix time in seconds at which the current code expires,
+    /// the end of the current time step
+    ///
+    /// returns `Error::InvalidKey` if the time step is 0,
+    /// or the current time is before t0
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    ///
+    /// let totp_key = TOTPKey {
+    ///     key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let valid_until = totp_key.valid_until().unwrap();
+    ///
+    /// assert_eq!(valid_until % 30, 0);
+    /// ```
+    pub fn valid_until(&self) -> Result<i64, error::Error> {
+        let step = self.get_step(crate::util::now_unix())?;
+
+        Ok(self.t0 + (step + 1) * self.time_step as i64)
+    }
+
+    /// get a value which displays the current code,
+    /// for printing the code with `{}`
+    ///
+    /// see `CodeDisplay`
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    ///
+    /// let totp_key = TOTPKey {
+    ///     key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// println!("code: {}", totp_key.display_code());
+    /// ```
+    pub fn display_code(&self) -> CodeDisplay<'_> {
+        CodeDisplay(self)
+    }
+
+    /// find the time step the code matched at,
+    /// checking `window` steps before and after the step of `unix`
+    ///
+    /// returns `None` if the code does not match any step in the window
+    ///
+    /// the returned step can be stored by the caller,
+    /// so a later submission matching the same (or an earlier) step
+    /// can be rejected as a replay
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    ///
+    /// let totp_key = TOTPKey {
+    ///     key: "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(totp_key.matched_window("not a code", 1_000_000, 1), None);
+    /// ```
+    pub fn matched_window(&self, code: &str, unix: i64, window: u8) -> Option<i64> {
+        self.match_step(code, unix, window, window)
+    }
+
+    /// verify the code at `unix`,
+    /// accepting codes up to `back` steps before and `ahead` steps after the current step
+    ///
+    /// besides whether the code matched,
+    /// the result carries the matched step and the clock drift in seconds,
+    /// which is negative if the code came from an earlier step
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    ///
+    /// let totp_key = TOTPKey {
+    ///     key: "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let res = totp_key.verify_detailed("not a code", 1_000_000, 1, 1);
+    ///
+    /// assert!(!res.matched);
+    /// assert_eq!(res.step, None);
+    /// assert_eq!(res.drift_seconds, None);
+    /// ```
+    pub fn verify_detailed(&self, code: &str, unix: i64, back: u8, ahead: u8) -> VerifyResult {
+        let current = self.get_step(unix);
+
+        match (current, self.match_step(code, unix, back, ahead)) {
+            (Ok(current), Some(step)) => VerifyResult {
+                matched: true,
+                step: Some(step),
+                drift_seconds: Some((step - current) * self.time_step as i64),
+            },
+            _ => VerifyResult::default(),
+        }
+    }
+
+    /// generate the codes for the steps around `center_time`,
+    /// from `before` steps before to `after` steps after the step of `center_time`
+    ///
+    /// each code is paired with the unix timestamp its step starts at,
+    /// the steps before t0 are left out,
+    /// returns `Error::InvalidKey` if `center_time` itself is before t0
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    ///
+    /// let totp_key = TOTPKey {
+    ///     key: "MFSWS5LGNBUXKZLBO5TGQ33JO5SWC2DGNF2WCZLIMZUXKZLXMFUGM2LVNFQWK53IMZUXK2A=".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let codes = totp_key.codes_in_window(1_000_000, 1, 2).unwrap();
+    ///
+    /// assert_eq!(codes.len(), 4);
+    /// assert_eq!(codes[0].0, 999_960);
+    /// assert_eq!(codes[1].0, 999_990);
+    /// assert_eq!(codes[3].0, 1_000_050);
+    /// ```
+    pub fn codes_in_window(
+        &self,
+        center_time: i64,
+        before: u8,
+        after: u8,
+    ) -> Result<Vec<(i64, String)>, error::Error> {
+        let current = self.get_step(center_time)?;
+
+        let mut codes = vec![];
+        for step in current - before as i64..=current + after as i64 {
+            if step < 0 {
+                continue;
+            }
+            let code = self.get_code_for_step(step)?;
+            codes.push((self.t0 + step * self.time_step as i64, code));
+        }
+
+        Ok(codes)
+    }
+
+    /// verify the code at the current time,
+    /// checking `skew` steps before and after the current step,
+    /// and return the step it matched at
+    ///
+    /// returns `Ok(None)` if the code does not match any step in the window,
+    /// and an error if the codes can not be generated,
+    /// e.g. `Error::InvalidKey` if the key is not valid base32
+    ///
+    /// the server can store the returned step,
+    /// and reject a later code matching the same or an earlier step as a replay
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    /// use libr2fa::Key;
+    ///
+    /// let mut totp_key = TOTPKey {
+    ///     key: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let code = totp_key.get_code().unwrap();
+    ///
+    /// assert!(totp_key.verify_returning_step(&code, 1).unwrap().is_some());
+    /// assert_eq!(totp_key.verify_returning_step("000000x", 1), Ok(None));
+    /// ```
+    pub fn verify_returning_step(&self, code: &str, skew: u8) -> Result<Option<i64>, error::Error> {
+        let unix = crate::util::now_unix();
+
+        // surface the errors, which `match_step` treats as not matching
+        self.get_code_value_for_step(self.get_step(unix)?)?;
+
+        Ok(self.match_step(code, unix, skew, skew))
+    }
+
+    /// find the step the code matched at,
+    /// checking from `back` steps before to `ahead` steps after the step of `unix`
+    fn match_step(&self, code: &str, unix: i64, back: u8, ahead: u8) -> Option<i64> {
+        let current = self.get_step(unix).ok()?;
+
+        for step in current - back as i64..=current + ahead as i64 {
+            if step < 0 {
+                continue;
+            }
+            if let Ok(expected) = self.get_code_for_step(step) {
+                if crate::util::constant_time_eq(&expected, code) {
+                    return Some(step);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// the result of verifying a TOTP code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct VerifyResult {
+    /// whether the code matched any step in the window
+    pub matched: bool,
+    /// the step the code matched at
+    pub step: Option<i64>,
+    /// the clock drift in seconds, `(matched_step - current_step) * time_step`
+    pub drift_seconds: Option<i64>,
+}
+
+/// builder for the TOTPKey,
+/// which validates the key on `build`
+///
+/// ```rust
+/// use libr2fa::TOTPKey;
+/// use libr2fa::HMACType;
+/// use libr2fa::Error;
+///
+/// let totp_key = TOTPKey::builder()
+///     .secret("HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ")
+///     .name("john.doe@email.com")
+///     .issuer("ACME Co")
+///     .digits(7)
+///     .period(60)
+///     .algorithm(HMACType::SHA256)
+///     .build();
+///
+/// assert!(totp_key.is_ok());
+/// let totp_key = totp_key.unwrap();
+/// assert_eq!(totp_key.digits, 7);
+/// assert_eq!(totp_key.time_step, 60);
+///
+/// let totp_key = TOTPKey::builder()
+///     .secret("HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ")
+///     .digits(9)
+///     .build();
+///
+/// assert_eq!(totp_key, Err(Error::InvalidDigits));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TOTPKeyBuilder {
+    key: TOTPKey,
+    min_secret_len: Option<usize>,
+    secret_encoding: SecretEncoding,
+}
+
+impl TOTPKeyBuilder {
+    /// set the secret, base32 encoded unless set otherwise by `secret_encoding`
+    pub fn secret(mut self, secret: &str) -> Self {
+        self.key.key = secret.to_string();
+        self
+    }
+
+    /// set the encoding of the secret, base32 by default
+    ///
+    /// the secret is stored base32 encoded in the built key
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    /// use libr2fa::SecretEncoding;
+    ///
+    /// let totp_key = TOTPKey::builder()
+    ///     .secret("YWJj")
+    ///     .secret_encoding(SecretEncoding::Base64)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(totp_key.key, "MFRGG===");
+    /// ```
+    pub fn secret_encoding(mut self, encoding: SecretEncoding) -> Self {
+        self.secret_encoding = encoding;
+        self
+    }
+
+    /// set the name
+    pub fn name(mut self, name: &str) -> Self {
+        self.key.name = name.to_string();
+        self
+    }
+
+    /// set the issuer
+    pub fn issuer(mut self, issuer: &str) -> Self {
+        self.key.issuer = Some(issuer.to_string());
+        self
+    }
+
+    /// set the digits, 6, 7 or 8
+    pub fn digits(mut self, digits: u8) -> Self {
+        self.key.digits = digits;
+        self
+    }
+
+    /// set the time step in seconds
+    pub fn period(mut self, period: u64) -> Self {
+        self.key.time_step = period;
+        self
+    }
+
+    /// set the hmac algorithm
+    pub fn algorithm(mut self, algorithm: HMACType) -> Self {
+        self.key.hmac_type = algorithm;
+        self
+    }
+
+    /// set the start time, t0
+    pub fn t0(mut self, t0: i64) -> Self {
+        self.key.t0 = t0;
+        self
+    }
+
+    /// reject secrets shorter than `MIN_SECRET_LENGTH` decoded bytes on `build`
+    ///
+    /// ```rust
+    /// use libr2fa::TOTPKey;
+    /// use libr2fa::Error;
+    ///
+    /// // 5 bytes
+    /// let totp_key = TOTPKey::builder().secret("GEZDGNBV").strict().build();
+    /// assert_eq!(totp_key, Err(Error::InvalidKey));
+    ///
+    /// let totp_key = TOTPKey::builder().secret("GEZDGNBV").build();
+    /// assert!(totp_key.is_ok());
+    /// ```
+    pub fn strict(self) -> Self {
+        self.min_secret_len(crate::MIN_SECRET_LENGTH)
+    }
+
+    /// reject secrets shorter than `len` decoded bytes on `build`
+    pub fn min_secret_len(mut self, len: usize) -> Self {
+        self.min_secret_len = Some(len);
+        self
+    }
+
+    /// validate and build the key
+    ///
+    /// returns `Error::InvalidDigits` if the digits is not 6, 7 or 8,
+    /// `Error::InvalidKey` if the secret is empty or not valid in its encoding,
+    /// shorter than the minimum length in strict mode, or if the period is 0
+    pub fn build(mut self) -> Result<TOTPKey, error::Error> {
+        if !(6..=8).contains(&self.key.digits) {
+            return Err(error::Error::InvalidDigits);
+        }
+        if self.key.key.is_empty() || self.key.time_step == 0 {
+            return Err(error::Error::InvalidKey);
+        }
+        if self.secret_encoding != SecretEncoding::Base32 {
+            let key = self.secret_encoding.decode(&self.key.key)?;
+            self.key.key = Secret::Raw(key).to_encoded()?;
+        }
+        let key = self.key.decode_key()?;
+        if let Some(min_secret_len) = self.min_secret_len {
+            if key.len() < min_secret_len {
+                return Err(error::Error::InvalidKey);
+            }
+        }
+
+        Ok(self.key)
+    }
+}
+
+/// displays the current code of a TOTP key, created by `TOTPKey::display_code`
+///
+/// the code is generated with `TOTPKey::get_code_at` when formatted,
+/// so it is always the code of the moment it is printed,
+/// if the code can not be generated, e.g. the secret is invalid,
+/// the error is displayed in angle brackets instead
+///
+/// `Key::get_code` takes `&mut self`, as generating a HOTP code advances the counter,
+/// which `Display` can not do, so there is no equivalent for HOTP keys
+#[derive(Debug, Clone, Copy)]
+pub struct CodeDisplay<'a>(pub &'a TOTPKey);
+
+impl std::fmt::Display for CodeDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0.get_code_at(crate::util::now_unix()) {
+            Ok(code) => write!(f, "{}", code),
+            Err(e) => write!(f, "<{}>", e),
+        }
+    }
+}
+
+impl TOTPKey {
+    /// create a builder for the key
+    pub fn builder() -> TOTPKeyBuilder {
+        TOTPKeyBuilder::default()
+    }
+}
+
+/// parse the key from a `otpauth://totp/` uri string
+///
+/// returns `Error::InvalidURI` if the uri is not a TOTP uri
+///
+/// ```rust
+/// use libr2fa::TOTPKey;
+/// use libr2fa::HMACType;
+///
+/// let key: TOTPKey = "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co&algorithm=SHA256&digits=7&period=60".parse().unwrap();
+///
+/// assert_eq!(key.digits, 7);
+/// assert_eq!(key.hmac_type, HMACType::SHA256);
+/// assert_eq!(key.time_step, 60);
+///
+/// let key = "otpauth://hotp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".parse::<TOTPKey>();
+///
+/// assert!(key.is_err());
+/// ```
+impl FromStr for TOTPKey {
+    type Err = error::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let uri = crate::URI::from_str(s)?;
+
+        Self::try_from(&uri)
+    }
+}
+
+/// build the key from a parsed uri struct, keeping the concrete type,
+/// unlike `OtpAuthKey::from_uri_struct`
+///
+/// returns `Error::InvalidURI` if the uri is not a TOTP uri
+///
+/// ```rust
+/// use libr2fa::TOTPKey;
+/// use libr2fa::URI;
+///
+/// let uri = URI::from("otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&period=60");
+///
+/// let key = TOTPKey::try_from(&uri).unwrap();
+/// assert_eq!(key.name, "john.doe@email.com");
+///
+/// let uri = URI::from("otpauth://hotp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ");
+///
+/// assert!(TOTPKey::try_from(&uri).is_err());
+/// ```
+impl TryFrom<&crate::URI> for TOTPKey {
+    type Error = error::Error;
+
+    fn try_from(uri: &crate::URI) -> Result<Self, Self::Error> {
+        if uri.key_type != crate::KeyType::TOTP {
+            return Err(error::Error::InvalidURI(format!(
+                "expected a totp uri, got a {} uri",
+                uri.key_type
+            )));
+        }
+
+        Ok(Self::from_uri(uri))
+    }
 }
 
 impl OtpAuthKey for TOTPKey {
@@ -84,36 +774,15 @@ impl OtpAuthKey for TOTPKey {
             period: Some(self.time_step),
             counter: None,
             key_type: crate::KeyType::TOTP,
+            // omit the default epoch to keep the uri standard
+            epoch: if self.t0 == 0 { None } else { Some(self.t0) },
+            encoder: None,
+            image: None,
         }
     }
 
     fn from_uri_struct(uri: &crate::URI) -> Result<Box<dyn Key>, crate::Error> {
-        let time_step = if let Some(time_step) = uri.period {
-            time_step
-        } else {
-            30
-        };
-        let digits = if let Some(digits) = uri.digits {
-            digits
-        } else {
-            6
-        };
-        let algorithm = if let Some(algorithm) = uri.algorithm {
-            algorithm
-        } else {
-            HMACType::default()
-        };
-
-        Ok(Box::from(TOTPKey {
-            name: uri.name.clone(),
-            issuer: uri.issuer.clone(),
-            key: uri.secret.clone(),
-            digits,
-            time_step,
-            t0: 0,
-            recovery_codes: Vec::default(),
-            hmac_type: algorithm,
-        }))
+        Ok(Box::from(Self::from_uri(uri)))
     }
 
     fn get_issuer(&self) -> Option<&str> {
@@ -123,29 +792,15 @@ impl OtpAuthKey for TOTPKey {
 
 impl Key for TOTPKey {
     fn get_code(&mut self) -> Result<String, error::Error> {
-        let raw = self.decode_key()?;
-        let c = (chrono::Utc::now().timestamp() - self.t0) / self.time_step as i64;
-        let c = c as u64;
-        let c = c.to_be_bytes();
-
-        let res = self.hmac_type.get_hash(raw.as_ref(), &c)?;
-        let offset: usize = (res[res.len() - 1] & 0x0f) as usize;
-
-        let code: u32 = (((res[offset] & 0x7f) as u32) << 24)
-            | ((res[offset + 1] as u32) << 16)
-            | ((res[offset + 2] as u32) << 8)
-            | (res[offset + 3] as u32);
-
-        // trim to the number of digits
-        let code = code % 10u32.pow(self.digits as u32);
+        self.get_code_from(&SystemTimeSource)
+    }
 
-        let mut code = code.to_string();
-        // padding 0
-        while code.len() < self.digits as usize {
-            code.insert(0, '0');
-        }
+    fn provisioning_uri(&self) -> Result<String, error::Error> {
+        Ok(self.to_uri_struct().to_string())
+    }
 
-        Ok(code)
+    fn sort_key(&self) -> (String, String) {
+        (self.issuer.clone().unwrap_or_default(), self.name.clone())
     }
 
     fn get_name(&self) -> &str {
@@ -160,10 +815,43 @@ impl Key for TOTPKey {
         crate::KeyType::TOTP
     }
 
+    fn get_digits(&self) -> u8 {
+        self.digits
+    }
+
+    fn get_algorithm(&self) -> Option<HMACType> {
+        Some(self.hmac_type)
+    }
+
+    fn get_interval(&self) -> Option<u64> {
+        Some(self.time_step)
+    }
+
+    fn get_counter(&self) -> Option<u64> {
+        None
+    }
+
+    fn get_code_at_time(&mut self, unix_seconds: i64) -> Result<String, error::Error> {
+        self.get_code_at(unix_seconds)
+    }
+
+    fn step_progress_from(&self, time: &dyn TimeSource) -> Option<f64> {
+        crate::util::step_progress(time.now_unix(), self.t0, self.time_step)
+    }
+
+    fn security_advice(&self) -> Vec<String> {
+        let secret_len = self.decode_key().ok().map(|key| key.len());
+        crate::util::security_advice(self, secret_len)
+    }
+
     fn set_name(&mut self, name: &str) {
         self.name = name.to_string();
     }
 
+    fn set_counter(&mut self, _counter: u64) {
+        // TOTP is time based and has no counter
+    }
+
     fn set_recovery_codes(&mut self, recovery_codes: Vec<String>) {
         self.recovery_codes = recovery_codes.to_vec();
     }
@@ -171,4 +859,12 @@ impl Key for TOTPKey {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Key> {
+        Box::new(self.clone())
+    }
 }
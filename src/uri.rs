@@ -1,5 +1,6 @@
 use std::fmt::Display;
 use std::fmt::Formatter;
+use std::str::FromStr;
 
 #[cfg(any(feature = "qrcodegen", feature = "qrcoderead"))]
 use std::path::PathBuf;
@@ -11,7 +12,6 @@ use once_cell::sync::Lazy;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
-#[cfg(any(feature = "qrcodegen", feature = "qrcoderead"))]
 use crate::error;
 
 use crate::HMACType;
@@ -20,13 +20,23 @@ use crate::KeyType;
 #[cfg(feature = "qrcodegen")]
 use image::GenericImage;
 
-static URI_DATA_REGEX: Lazy<regex::Regex> =
-    Lazy::new(|| Regex::new(r"(secret|algorithm|digits|period|counter|issuer)=[^\s&]*").unwrap());
+static URI_DATA_REGEX: Lazy<regex::Regex> = Lazy::new(|| {
+    Regex::new(r"(secret|algorithm|digits|period|counter|issuer|encoder|epoch|t0|image)=[^\s&]*")
+        .unwrap()
+});
 
 /// the URI struct
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+///
+/// the parser does not depend on the order of the query parameters,
+/// so uris with the same parameters in different orders parse to equal structs,
+/// the serializer always writes the parameters in the same order
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
 pub struct URI {
     /// name
+    ///
+    /// the account name, without the issuer prefix of the label,
+    /// a label `Issuer:Account` is parsed to the name `Account`
+    /// and the issuer `Issuer` if the `issuer` parameter is absent
     pub name: String,
     /// type
     pub key_type: KeyType,
@@ -56,8 +66,24 @@ pub struct URI {
     ///
     /// The time step in seconds. This is only used for TOTP.
     pub period: Option<u64>,
+    /// epoch
+    ///
+    /// The start time (t0) in unix seconds, serialized as `epoch`,
+    /// `t0` is also accepted when parsing.
+    /// This is only used for TOTP, and defaults to 0 when absent.
+    pub epoch: Option<i64>,
     /// issuer
     pub issuer: Option<String>,
+    /// encoder
+    ///
+    /// some exporters encode steam accounts as totp uris with `encoder=steam`,
+    /// which marks the uri as key type steam when parsed
+    pub encoder: Option<String>,
+    /// image
+    ///
+    /// the url of a logo for the account, which can be displayed by the ui,
+    /// percent-encoded in the uri
+    pub image: Option<String>,
 }
 
 impl URI {
@@ -103,19 +129,27 @@ impl URI {
     /// ```
     #[cfg(feature = "qrcoderead")]
     pub fn from_qr_code(path: &str) -> Result<Self, error::Error> {
-        // test if it is a valid path
-        let path = PathBuf::from(path);
-        if !path.exists() {
-            return Err(error::Error::InvalidPath(
-                "target path does not exists".to_string(),
-            ));
-        }
-        // if path is not a file
-        if path.is_dir() {
-            return Err(error::Error::InvalidPath(
-                "target path is not a file".to_string(),
-            ));
-        }
+        let decoded = URI::decode_qr_payload(path)?;
+
+        Ok(URI::from(decoded))
+    }
+
+    /// Read the raw text of a QR code, without parsing it
+    ///
+    /// `from_qr_code` gives an empty URI if the text is not an otpauth uri,
+    /// this gives the text itself, e.g. to inspect a malformed export
+    ///
+    /// ```rust
+    /// use libr2fa::URI;
+    ///
+    /// let payload = URI::decode_qr_payload("public/uri_qrcode_test.png");
+    /// assert!(payload.is_ok());
+    ///
+    /// assert!(payload.unwrap().starts_with("otpauth://totp/"));
+    /// ```
+    #[cfg(feature = "qrcoderead")]
+    pub fn decode_qr_payload(path: &str) -> Result<String, error::Error> {
+        let path = check_qr_code_path(path)?;
 
         // read the file
         let img = image::open(path);
@@ -127,25 +161,70 @@ impl URI {
         }
         let img = img.unwrap().to_luma8();
 
-        // check https://docs.rs/rqrr/latest/rqrr/
-        let mut img = rqrr::PreparedImage::prepare(img);
-        let grids = img.detect_grids();
-        if grids.is_empty() {
-            return Err(error::Error::InvalidPath(
-                "could not detect QR code".to_string(),
+        decode_qr_code(img)
+    }
+
+    /// Create the URIs from the QR codes in the frames of an animated gif,
+    /// in frame order
+    ///
+    /// some exporters split large exports across the frames of an animated QR code,
+    /// every frame must contain a QR code
+    ///
+    /// ```rust
+    /// use libr2fa::URI;
+    ///
+    /// let uris = URI::from_qr_animated_gif("public/uri_qrcode_animated_test.gif");
+    /// assert!(uris.is_ok());
+    /// let uris = uris.unwrap();
+    ///
+    /// assert_eq!(uris.len(), 2);
+    /// assert_eq!(uris[0].name, "john.doe@email.com");
+    /// assert_eq!(uris[1].name, "jane.doe@email.com");
+    /// ```
+    #[cfg(feature = "qrcoderead")]
+    pub fn from_qr_animated_gif(path: &str) -> Result<Vec<Self>, error::Error> {
+        use image::AnimationDecoder;
+
+        let path = check_qr_code_path(path)?;
+
+        let file = std::fs::File::open(&path);
+        if let Err(e) = file {
+            return Err(error::Error::IOError(
+                "could not open file".to_string(),
+                path.display().to_string(),
+                e.to_string(),
             ));
         }
-        let grid = &grids[0];
-        let decoded = grid.decode();
-        if let Err(e) = decoded {
+        let file = std::io::BufReader::new(file.unwrap());
+
+        let decoder = image::codecs::gif::GifDecoder::new(file);
+        if let Err(e) = decoder {
             return Err(error::Error::InvalidPath(format!(
-                "could not decode QR code: {}",
+                "could not read gif: {}",
                 e
             )));
         }
-        let (_, decoded) = decoded.unwrap();
+        let decoder = decoder.unwrap();
+
+        let mut uris = vec![];
+        for (i, frame) in decoder.into_frames().enumerate() {
+            if let Err(e) = frame {
+                return Err(error::Error::InvalidPath(format!(
+                    "could not read frame {}: {}",
+                    i, e
+                )));
+            }
+            let img = image::DynamicImage::ImageRgba8(frame.unwrap().into_buffer()).to_luma8();
 
-        Ok(URI::from(decoded))
+            let decoded = decode_qr_code(img);
+            if let Err(e) = decoded {
+                return Err(error::Error::InvalidPath(format!("frame {}: {}", i, e)));
+            }
+
+            uris.push(URI::from(decoded.unwrap()));
+        }
+
+        Ok(uris)
     }
 
     /// Convert the URI to a QR code,
@@ -201,20 +280,139 @@ impl URI {
     }
 }
 
+/// check the path exists and is a file
+#[cfg(feature = "qrcoderead")]
+fn check_qr_code_path(path: &str) -> Result<PathBuf, error::Error> {
+    // test if it is a valid path
+    let path = PathBuf::from(path);
+    if !path.exists() {
+        return Err(error::Error::InvalidPath(
+            "target path does not exists".to_string(),
+        ));
+    }
+    // if path is not a file
+    if path.is_dir() {
+        return Err(error::Error::InvalidPath(
+            "target path is not a file".to_string(),
+        ));
+    }
+
+    Ok(path)
+}
+
+/// decode the text of the first QR code in the image
+#[cfg(feature = "qrcoderead")]
+fn decode_qr_code(img: image::GrayImage) -> Result<String, error::Error> {
+    // check https://docs.rs/rqrr/latest/rqrr/
+    let mut img = rqrr::PreparedImage::prepare(img);
+    let grids = img.detect_grids();
+    if grids.is_empty() {
+        return Err(error::Error::InvalidPath(
+            "could not detect QR code".to_string(),
+        ));
+    }
+    let grid = &grids[0];
+    let decoded = grid.decode();
+    if let Err(e) = decoded {
+        return Err(error::Error::InvalidPath(format!(
+            "could not decode QR code: {}",
+            e
+        )));
+    }
+    let (_, decoded) = decoded.unwrap();
+
+    Ok(decoded)
+}
+
 impl Display for URI {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", String::from(self.clone()))
     }
 }
 
+/// the options of an encoded qrcode image
+#[cfg(feature = "qrcodegen")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QrCodeOptions {
+    /// the width and height of the image in pixels,
+    /// default 2048
+    pub size: u32,
+    /// the width of the white border around the code in modules,
+    /// default 4, the minimum of the qrcode spec
+    pub border: u32,
+}
+
+#[cfg(feature = "qrcodegen")]
+impl Default for QrCodeOptions {
+    fn default() -> Self {
+        Self {
+            size: 2048,
+            border: 4,
+        }
+    }
+}
+
 #[cfg(feature = "qrcodegen")]
 impl From<URI> for DynamicImage {
     fn from(value: URI) -> Self {
-        let uri = String::from(value);
+        value.to_qr_image(QrCodeOptions::default())
+    }
+}
+
+#[cfg(feature = "qrcodegen")]
+impl URI {
+    /// Encode the URI as a QR code PNG, and write it to `w`
+    ///
+    /// the image is written directly,
+    /// e.g. to the body of a response or to stdout
+    ///
+    /// ```rust
+    /// use libr2fa::URI;
+    /// use libr2fa::QrCodeOptions;
+    ///
+    /// let uri = URI::new_from_uri(
+    ///     "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co".to_string()
+    /// );
+    ///
+    /// let mut png = vec![];
+    /// uri.write_qr_png(&mut png, QrCodeOptions { size: 512, ..Default::default() }).unwrap();
+    ///
+    /// assert!(png.starts_with(b"\x89PNG"));
+    /// ```
+    pub fn write_qr_png<W: std::io::Write>(
+        &self,
+        w: &mut W,
+        opts: QrCodeOptions,
+    ) -> Result<(), error::Error> {
+        use image::ImageEncoder;
+
+        let img = self.to_qr_image(opts).to_luma8();
+
+        let encoder = image::codecs::png::PngEncoder::new(w);
+        let res = encoder.write_image(
+            img.as_raw(),
+            img.width(),
+            img.height(),
+            image::ColorType::L8,
+        );
+        if let Err(e) = res {
+            return Err(error::Error::IOError(
+                "could not write qrcode png".to_string(),
+                "".to_string(),
+                e.to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// encode the URI as a QR code image
+    fn to_qr_image(&self, opts: QrCodeOptions) -> DynamicImage {
+        let uri = self.to_string();
         let qr = qrcodegen::QrCode::encode_text(&uri, qrcodegen::QrCodeEcc::High).unwrap();
 
         let size = qr.size() as u32;
-        let border = 4;
+        let border = opts.border;
         let mut res =
             image::DynamicImage::new_luma8(size + border + border, size + border + border);
 
@@ -237,7 +435,75 @@ impl From<URI> for DynamicImage {
             }
         }
 
-        res.resize(2048, 2048, image::imageops::FilterType::Nearest)
+        res.resize(opts.size, opts.size, image::imageops::FilterType::Nearest)
+    }
+}
+
+/// chainable setters, for building a uri to provision a key, e.g. as a qrcode
+///
+/// ```rust
+/// use libr2fa::URI;
+/// use libr2fa::KeyType;
+/// use libr2fa::HMACType;
+///
+/// let uri = URI::default()
+///     .with_key_type(KeyType::HOTP)
+///     .with_name("john.doe@email.com")
+///     .with_issuer("ACME Co")
+///     .with_secret("HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ")
+///     .with_algorithm(HMACType::SHA256)
+///     .with_digits(7)
+///     .with_counter(7);
+///
+/// assert_eq!(uri.to_string(), "otpauth://hotp/ACME+Co%3Ajohn.doe%40email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&algorithm=SHA256&digits=7&counter=7&issuer=ACME+Co");
+/// ```
+impl URI {
+    /// set the key type
+    pub fn with_key_type(mut self, key_type: KeyType) -> Self {
+        self.key_type = key_type;
+        self
+    }
+
+    /// set the account name
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// set the issuer
+    pub fn with_issuer(mut self, issuer: &str) -> Self {
+        self.issuer = Some(issuer.to_string());
+        self
+    }
+
+    /// set the base32 encoded secret
+    pub fn with_secret(mut self, secret: &str) -> Self {
+        self.secret = secret.to_string();
+        self
+    }
+
+    /// set the hmac algorithm
+    pub fn with_algorithm(mut self, algorithm: HMACType) -> Self {
+        self.algorithm = Some(algorithm);
+        self
+    }
+
+    /// set the digits
+    pub fn with_digits(mut self, digits: u8) -> Self {
+        self.digits = Some(digits);
+        self
+    }
+
+    /// set the time step in seconds, for TOTP
+    pub fn with_period(mut self, period: u64) -> Self {
+        self.period = Some(period);
+        self
+    }
+
+    /// set the counter, for HOTP
+    pub fn with_counter(mut self, counter: u64) -> Self {
+        self.counter = Some(counter);
+        self
     }
 }
 
@@ -254,56 +520,121 @@ impl From<URI> for DynamicImage {
 /// ```
 impl From<URI> for String {
     fn from(value: URI) -> Self {
-        match value.key_type {
+        value.serialize(false, false)
+    }
+}
+
+impl URI {
+    /// Convert the URI to a string, omitting the parameters equal to their defaults,
+    /// `algorithm=SHA1`, `digits=6`, `period=30` and `epoch=0`
+    ///
+    /// the shorter uri gives a less dense qrcode, which is easier to scan
+    ///
+    /// ```rust
+    /// use libr2fa::URI;
+    ///
+    /// let uri = URI::new_from_uri(
+    ///     "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co&algorithm=SHA1&digits=6&period=30".to_string()
+    /// );
+    ///
+    /// assert_eq!(uri.to_minimal_uri(), "otpauth://totp/ACME+Co%3Ajohn.doe%40email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME+Co");
+    /// ```
+    pub fn to_minimal_uri(&self) -> String {
+        self.serialize(true, false)
+    }
+
+    /// Convert the URI to a string like `to_string`,
+    /// but encode the spaces of the label and the parameters as `%20` rather than `+`,
+    /// which some parsers do not decode
+    ///
+    /// ```rust
+    /// use libr2fa::URI;
+    ///
+    /// let uri = URI::new_from_uri(
+    ///     "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co".to_string()
+    /// );
+    ///
+    /// assert_eq!(uri.to_percent_encoded_uri(), "otpauth://totp/ACME%20Co%3Ajohn.doe%40email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&algorithm=SHA1&digits=6&issuer=ACME%20Co");
+    /// ```
+    pub fn to_percent_encoded_uri(&self) -> String {
+        self.serialize(false, true)
+    }
+
+    /// serialize the uri, if `minimal` the parameters equal to their defaults are omitted,
+    /// if `percent_spaces` spaces are encoded as `%20` rather than `+`
+    fn serialize(&self, minimal: bool, percent_spaces: bool) -> String {
+        let encode = |s: &str| {
+            let encoded = url::form_urlencoded::byte_serialize(s.as_bytes()).collect::<String>();
+            // a literal `+` is encoded as `%2B`, so every `+` left is a space
+            if percent_spaces {
+                encoded.replace('+', "%20")
+            } else {
+                encoded
+            }
+        };
+
+        match self.key_type {
             #[cfg(feature = "steam")]
             KeyType::Steam => {
                 format!(
-                    "otpauth://totp/Steam:{}?secret={}&issuer=Steam",
-                    value.name, value.secret
+                    "otpauth://totp/Steam:{}?secret={}&issuer=Steam&encoder=steam",
+                    encode(&self.name),
+                    self.secret
                 )
             }
             _ => {
                 let mut uri = String::new();
 
                 uri.push_str("otpauth://");
-                uri.push_str(value.key_type.to_string().as_str());
+                uri.push_str(self.key_type.to_string().as_str());
                 uri.push('/');
-                let name =
-                    url::form_urlencoded::byte_serialize(value.name.as_bytes()).collect::<String>();
-                uri.push_str(&name);
+                if let Some(issuer) = self.issuer.as_ref().filter(|issuer| !issuer.is_empty()) {
+                    uri.push_str(&encode(issuer));
+                    uri.push_str("%3A");
+                } else if self.name.contains(':') {
+                    // an empty issuer prefix, so the colon is not parsed as the separator
+                    uri.push_str("%3A");
+                }
+                uri.push_str(&encode(&self.name));
 
                 let mut keys = vec![];
-                let secret = format!("secret={}", value.secret);
+                let secret = format!("secret={}", self.secret);
                 keys.push(secret);
-                let algorithm = if let Some(algorithm) = value.algorithm {
-                    algorithm
-                } else {
-                    HMACType::default()
-                };
-                let algorithm = format!("algorithm={}", algorithm.to_string().to_ascii_uppercase());
-                keys.push(algorithm);
-                let digits = if let Some(digits) = value.digits {
-                    digits
-                } else {
-                    6
-                };
-                let digits = format!("digits={}", digits);
-                keys.push(digits);
-                if value.counter.is_some() {
-                    let counter = format!("counter={}", value.counter.unwrap());
+                let algorithm = self.algorithm.unwrap_or_default();
+                if !minimal || algorithm != HMACType::default() {
+                    let algorithm =
+                        format!("algorithm={}", algorithm.to_string().to_ascii_uppercase());
+                    keys.push(algorithm);
+                }
+                let digits = self.digits.unwrap_or(6);
+                if !minimal || digits != 6 {
+                    let digits = format!("digits={}", digits);
+                    keys.push(digits);
+                }
+                if let Some(counter) = self.counter {
+                    let counter = format!("counter={}", counter);
                     keys.push(counter);
                 }
-                if value.period.is_some() {
-                    let period = format!("period={}", value.period.unwrap());
+                if let Some(period) = self.period.filter(|period| !minimal || *period != 30) {
+                    let period = format!("period={}", period);
                     keys.push(period);
                 }
-                if value.issuer.is_some() {
-                    let issuer =
-                        url::form_urlencoded::byte_serialize(value.issuer.unwrap().as_bytes())
-                            .collect::<String>();
-                    let issuer = format!("issuer={}", issuer);
+                if let Some(epoch) = self.epoch.filter(|epoch| !minimal || *epoch != 0) {
+                    let epoch = format!("epoch={}", epoch);
+                    keys.push(epoch);
+                }
+                if let Some(issuer) = &self.issuer {
+                    let issuer = format!("issuer={}", encode(issuer));
                     keys.push(issuer);
                 }
+                if let Some(encoder) = &self.encoder {
+                    let encoder = format!("encoder={}", encode(encoder));
+                    keys.push(encoder);
+                }
+                if let Some(image) = &self.image {
+                    let image = format!("image={}", encode(image));
+                    keys.push(image);
+                }
 
                 uri.push('?');
                 uri.push_str(keys.join("&").as_str());
@@ -314,6 +645,103 @@ impl From<URI> for String {
     }
 }
 
+impl URI {
+    /// Parse a URI string like `from_str`,
+    /// but accept contradictory parameters,
+    /// a TOTP uri with a `counter` or a HOTP uri with a `period`,
+    /// the key type then decides which one is used
+    ///
+    /// ```rust
+    /// use libr2fa::URI;
+    ///
+    /// let uri = "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&counter=7";
+    ///
+    /// assert!(uri.parse::<URI>().is_err());
+    /// assert!(URI::from_str_lenient(uri).is_ok());
+    /// ```
+    pub fn from_str_lenient(s: &str) -> Result<Self, error::Error> {
+        let uri = URI::from(s);
+        uri.validate(s, true)?;
+
+        Ok(uri)
+    }
+
+    /// check the uri parsed from `s` is usable,
+    /// if not `lenient` contradictory parameters are rejected as well
+    fn validate(&self, s: &str, lenient: bool) -> Result<(), error::Error> {
+        if self.secret.is_empty() {
+            return Err(error::Error::InvalidURI("missing secret".to_string()));
+        }
+        // `URI::from` clamps the digits, so check the parameter as it was given
+        if matches!(self.key_type, KeyType::HOTP | KeyType::TOTP) {
+            if let Some(digits) = raw_param(s, "digits") {
+                if !matches!(digits.parse::<u8>(), Ok(6..=8)) {
+                    return Err(error::Error::InvalidURI(format!(
+                        "invalid digits {}, expected 6, 7 or 8",
+                        digits
+                    )));
+                }
+            }
+        }
+        // a period of 0 has no time steps
+        if self.key_type == KeyType::TOTP && self.period == Some(0) {
+            return Err(error::Error::InvalidURI("invalid period 0".to_string()));
+        }
+        if lenient {
+            return Ok(());
+        }
+
+        match self.key_type {
+            KeyType::TOTP if self.counter.is_some() => Err(error::Error::InvalidURI(
+                "counter is not allowed for totp".to_string(),
+            )),
+            KeyType::HOTP if self.period.is_some() => Err(error::Error::InvalidURI(
+                "period is not allowed for hotp".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// get the raw value of the last `key` parameter of the uri string
+fn raw_param<'a>(s: &'a str, key: &str) -> Option<&'a str> {
+    URI_DATA_REGEX
+        .find_iter(s)
+        .filter_map(|cap| cap.as_str().split_once('='))
+        .filter(|(k, _)| *k == key)
+        .map(|(_, value)| value)
+        .last()
+}
+
+/// Parse a URI string, checking that it is usable
+///
+/// unlike `URI::from`, which never fails,
+/// this returns `Error::InvalidURI` if the secret is missing,
+/// if the digits of a HOTP or TOTP uri are not a number from 6 to 8,
+/// or if a TOTP uri has a `counter` or a HOTP uri has a `period`,
+/// see `URI::from_str_lenient` to accept the latter
+///
+/// ```rust
+/// use libr2fa::URI;
+/// use libr2fa::Error;
+///
+/// let uri = "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ".parse::<URI>();
+/// assert!(uri.is_ok());
+///
+/// let uri = "otpauth://totp/ACME%20Co:john.doe@email.com?issuer=ACME%20Co".parse::<URI>();
+/// assert_eq!(uri, Err(Error::InvalidURI("missing secret".to_string())));
+/// ```
+impl FromStr for URI {
+    type Err = error::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let uri = URI::from(s);
+        uri.validate(s, false)?;
+
+        Ok(uri)
+    }
+}
+
 impl From<String> for URI {
     fn from(value: String) -> Self {
         URI::from(value.as_str())
@@ -324,7 +752,11 @@ impl From<&str> for URI {
     fn from(value: &str) -> Self {
         let mut uri = URI::default();
 
-        let key_type = value.replace("otpauth://", "");
+        // the scheme is case insensitive, and may be left out, `totp/Label?secret=...`
+        let key_type = match value.get(..10) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("otpauth://") => &value[10..],
+            _ => value,
+        };
         let key_type = key_type.split('/').collect::<Vec<&str>>();
         if key_type.len() < 2 {
             return uri;
@@ -333,10 +765,6 @@ impl From<&str> for URI {
         let key_type = key_type[0];
         uri.key_type = KeyType::from(key_type);
 
-        if name.to_uppercase().starts_with("steam") {
-            uri.key_type = KeyType::Steam;
-        }
-
         let name = if name.get(0..1) == Some("?") {
             "".to_string()
         } else {
@@ -348,7 +776,6 @@ impl From<&str> for URI {
 
             name
         };
-        uri.name = name;
 
         let caps = URI_DATA_REGEX.captures_iter(value);
 
@@ -365,7 +792,8 @@ impl From<&str> for URI {
             }
             let cap = cap.unwrap().as_str();
 
-            let cap = cap.split('=').collect::<Vec<&str>>();
+            // only split at the first `=`, the value may contain more
+            let cap = cap.splitn(2, '=').collect::<Vec<&str>>();
             if cap.len() != 2 {
                 continue;
             }
@@ -399,6 +827,12 @@ impl From<&str> for URI {
                         uri.counter = Some(counter);
                     }
                 }
+                "epoch" | "t0" => {
+                    let epoch = value.parse::<i64>();
+                    if let Ok(epoch) = epoch {
+                        uri.epoch = Some(epoch);
+                    }
+                }
                 "issuer" => {
                     let issuer = value.to_string();
                     let issuer: String = url::form_urlencoded::parse(issuer.as_bytes())
@@ -407,10 +841,52 @@ impl From<&str> for URI {
 
                     uri.issuer = Some(issuer);
                 }
+                "encoder" => {
+                    // only a totp uri can carry a steam key, as written by `to_compat_uri`,
+                    // the counter of a hotp uri would be lost
+                    #[cfg(feature = "steam")]
+                    if value.eq_ignore_ascii_case("steam") && uri.key_type == KeyType::TOTP {
+                        uri.key_type = KeyType::Steam;
+                    }
+
+                    uri.encoder = Some(value.to_string());
+                }
+                "image" => {
+                    let image: String = url::form_urlencoded::parse(value.as_bytes())
+                        .map(|(key, val)| [key, val].concat())
+                        .collect();
+
+                    uri.image = Some(image);
+                }
                 _ => {}
             }
         }
 
+        // the label may be prefixed with the issuer, `Issuer:Account`,
+        // a prefix equal to the issuer parameter is stripped whole, as it may contain a colon
+        let account = uri
+            .issuer
+            .as_ref()
+            .and_then(|issuer| name.strip_prefix(issuer.as_str())?.strip_prefix(':'));
+        let (label_issuer, name) = match (account, name.split_once(':')) {
+            (Some(account), _) => (String::new(), account.trim_start().to_string()),
+            (None, Some((issuer, account))) => {
+                (issuer.to_string(), account.trim_start().to_string())
+            }
+            (None, None) => (String::new(), name),
+        };
+        uri.name = name;
+
+        if uri.issuer.is_none() && !label_issuer.is_empty() {
+            uri.issuer = Some(label_issuer);
+        }
+
+        // never fail, clamp unusable digits to the valid range,
+        // the fallible parsers reject them instead
+        if matches!(uri.key_type, KeyType::HOTP | KeyType::TOTP) {
+            uri.digits = uri.digits.map(|digits| digits.clamp(6, 8));
+        }
+
         uri
     }
 }
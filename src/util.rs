@@ -0,0 +1,125 @@
+use subtle::ConstantTimeEq;
+
+use crate::{error, HMACType, Key};
+
+/// get the current unix time in seconds
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn now_unix() -> i64 {
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH);
+
+    match now {
+        Ok(now) => now.as_secs() as i64,
+        // the clock is set before the unix epoch
+        Err(e) => -(e.duration().as_secs() as i64),
+    }
+}
+
+/// get the current unix time in seconds
+///
+/// `std::time` is not available on `wasm32-unknown-unknown`,
+/// so the time is read from the javascript `Date`
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn now_unix() -> i64 {
+    (js_sys::Date::now() / 1000.0) as i64
+}
+
+/// compare two codes in constant time
+///
+/// the bytes are compared one by one without returning early on the first difference,
+/// so the time taken does not leak how much of the code was correct,
+/// only the length is compared directly, as it is not secret
+pub(crate) fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}
+
+/// the dynamic truncation of rfc 4226,
+/// take 4 bytes of the digest at the offset given by its last byte, as a 31 bit integer
+///
+/// returns `Error::InvalidKey` if the 4 bytes at the offset are past the end of the digest,
+/// which can not happen for the 20 byte or longer sha digests
+pub(crate) fn dynamic_truncate(res: &[u8]) -> Result<u32, error::Error> {
+    if res.is_empty() {
+        return Err(error::Error::InvalidKey);
+    }
+    let offset: usize = (res[res.len() - 1] & 0x0f) as usize;
+    if offset + 3 >= res.len() {
+        return Err(error::Error::InvalidKey);
+    }
+
+    let code: u32 = (((res[offset] & 0x7f) as u32) << 24)
+        | ((res[offset + 1] as u32) << 16)
+        | ((res[offset + 2] as u32) << 8)
+        | (res[offset + 3] as u32);
+
+    Ok(code)
+}
+
+/// the base-N truncation of steam guard and similar vendor codes,
+/// the dynamic truncation of rfc 4226, then `length` characters of `alphabet`,
+/// the least significant digit in base `alphabet.len()` first
+///
+/// returns an empty string if the alphabet is empty,
+/// or the hash is too short to truncate, which none of the hmac digests are
+pub(crate) fn truncate_to_alphabet(hash: &[u8], length: usize, alphabet: &[u8]) -> String {
+    if alphabet.is_empty() {
+        return String::new();
+    }
+    let code_point = dynamic_truncate(hash);
+    if code_point.is_err() {
+        return String::new();
+    }
+    let mut code_point = code_point.unwrap();
+
+    let mut code = String::with_capacity(length);
+    for _ in 0..length {
+        code.push(alphabet[code_point as usize % alphabet.len()] as char);
+        code_point /= alphabet.len() as u32;
+    }
+
+    code
+}
+
+/// the fraction of the time step elapsed at `unix`, for `Key::step_progress_from`
+///
+/// `None` if the time step is 0,
+/// clamped below 1.0, which a step never reaches before the next one starts
+pub(crate) fn step_progress(unix: i64, t0: i64, time_step: u64) -> Option<f64> {
+    if time_step == 0 {
+        return None;
+    }
+    let time_step = time_step as i64;
+    let elapsed = (unix - t0).rem_euclid(time_step);
+
+    let progress = elapsed as f64 / time_step as f64;
+
+    Some(progress.min(1.0 - f64::EPSILON))
+}
+
+/// the advice of `Key::security_advice` for a HOTP or TOTP key,
+/// `secret_len` is the length of the decoded secret, if it can be decoded
+pub(crate) fn security_advice<K: Key + ?Sized>(key: &K, secret_len: Option<usize>) -> Vec<String> {
+    let mut advice = vec![];
+
+    if let Some(HMACType::SHA1) = key.get_algorithm() {
+        advice.push("uses SHA1; SHA256 or SHA512 are stronger".to_string());
+    }
+    let digits = key.get_digits();
+    if digits < 8 {
+        advice.push(format!("only {} digits, 8 are harder to guess", digits));
+    }
+    if let Some(interval) = key.get_interval().filter(|interval| *interval > 30) {
+        advice.push(format!(
+            "a {} second period keeps each code valid for longer than the usual 30",
+            interval
+        ));
+    }
+    if let Some(len) = secret_len.filter(|len| *len < crate::MIN_SECRET_LENGTH) {
+        advice.push(format!(
+            "the secret is only {} bytes, at least {} are recommended",
+            len,
+            crate::MIN_SECRET_LENGTH
+        ));
+    }
+
+    advice
+}